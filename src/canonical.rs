@@ -0,0 +1,154 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use types::{Node, Span, StringStatus};
+
+// Why a tree has no canonical form
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CanonicalError {
+    // A key appearing twice in an Object, with the span of the second member
+    DuplicateKey(String, Span),
+    // A number outside the range of an IEEE 754 double
+    UnrepresentableNumber(Span),
+    // A string or key with an invalid escape, control character or lone
+    // surrogate
+    InvalidString(Span),
+}
+
+impl CanonicalError {
+    pub fn span(&self) -> Span {
+        match *self {
+            CanonicalError::DuplicateKey(_, span)
+            | CanonicalError::UnrepresentableNumber(span)
+            | CanonicalError::InvalidString(span) => span,
+        }
+    }
+}
+
+pub fn write_string(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < '\u{20}' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// Serializes a double the way ECMAScript's Number.prototype.toString does
+// (ECMA-262, section 7.1.12.1)
+fn write_number(value: f64, out: &mut String) {
+    if value == 0.0 {
+        out.push('0');
+        return;
+    }
+    if value < 0.0 {
+        out.push('-');
+    }
+
+    // Rust prints the shortest digits that round-trip, as ECMAScript does
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_at(scientific.find('e').unwrap());
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i64;
+    let n = exponent[1..].parse::<i64>().unwrap() + 1;
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend((0..n - k).map(|_| '0'));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend((0..-n).map(|_| '0'));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push('e');
+        out.push(if n > 0 { '+' } else { '-' });
+        out.push_str(&(n - 1).abs().to_string());
+    }
+}
+
+fn utf16_cmp(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+fn write_node(node: &Node, out: &mut String) -> Result<(), CanonicalError> {
+    match *node {
+        Node::Object { ref children, .. } => {
+            let mut seen = HashSet::new();
+            let mut members = Vec::with_capacity(children.len());
+            for property in children {
+                if let StringStatus::Invalid(_) = property.key.status {
+                    return Err(CanonicalError::InvalidString(property.key.span));
+                }
+                let key = property.key.decoded();
+                if !seen.insert(key.clone()) {
+                    return Err(CanonicalError::DuplicateKey(key.into_owned(), property.span));
+                }
+                members.push((key, &property.value));
+            }
+            members.sort_by(|a, b| utf16_cmp(&a.0, &b.0));
+
+            out.push('{');
+            for (i, &(ref key, value)) in members.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_node(value, out)?;
+            }
+            out.push('}');
+        }
+        Node::Array { ref children, .. } => {
+            out.push('[');
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_node(child, out)?;
+            }
+            out.push(']');
+        }
+        Node::String {
+            status: StringStatus::Invalid(_),
+            span,
+            ..
+        } => return Err(CanonicalError::InvalidString(span)),
+        Node::String { .. } => write_string(&node.decoded().unwrap(), out),
+        // Numbers that overflow to infinity, or underflow to zero, have no
+        // double to print
+        Node::Number { span, .. } => match node.as_f64_rounded() {
+            Ok(value) => write_number(value, out),
+            Err(_) => return Err(CanonicalError::UnrepresentableNumber(span)),
+        },
+        Node::Boolean { ref raw, .. } | Node::Null { ref raw, .. } => out.push_str(raw),
+    }
+    Ok(())
+}
+
+// Serializes a tree with the JSON Canonicalization Scheme (RFC 8785): no
+// whitespace, Object members sorted by the UTF-16 code units of their keys,
+// numbers as ECMAScript prints doubles and strings with minimal escaping.
+// Equal documents give byte-identical output, for signing and hashing.
+pub fn canonicalize(node: &Node) -> Result<String, CanonicalError> {
+    let mut out = String::new();
+    write_node(node, &mut out)?;
+    Ok(out)
+}
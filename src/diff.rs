@@ -0,0 +1,369 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use canonical::write_string;
+use pointer::escape;
+use types::{Node, Property, Span};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    // A scalar with a different value
+    Changed,
+    // A value replaced by one of another kind
+    TypeChanged,
+    // An Array element now at another index, with its pointer in the old
+    // document
+    Moved { from: String },
+    // An Object with its members in a different order
+    Reordered,
+}
+
+// One difference between two documents. `pointer` is where the change is in
+// the new document, or in the old one for removals. Added and removed
+// Object members carry the span of the whole member, everything else the
+// spans of the values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub pointer: String,
+    pub old: Option<Span>,
+    pub new: Option<Span>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DiffOptions {
+    // Objects with the same members in another order are equal
+    pub ignore_key_order: bool,
+    // Numbers are compared by value, so `1`, `1.0` and `1e0` are equal.
+    // Otherwise they have to be written the same way.
+    pub numeric: bool,
+}
+
+fn child(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, escape(key))
+}
+
+// The members of an Object by their unescaped key, dropping all but the
+// last of any duplicates
+struct Members<'a> {
+    // In document order
+    order: Vec<(Cow<'a, str>, &'a Property)>,
+    // Each key's place in `order`
+    places: HashMap<Cow<'a, str>, usize>,
+}
+
+impl<'a> Members<'a> {
+    fn new(children: &'a [Property]) -> Members<'a> {
+        let keys: Vec<Cow<str>> = children.iter().map(|property| property.key.decoded()).collect();
+        let mut last = HashMap::with_capacity(keys.len());
+        for (i, key) in keys.iter().enumerate() {
+            last.insert(key.clone(), i);
+        }
+        let mut members = Members {
+            order: Vec::with_capacity(last.len()),
+            places: HashMap::with_capacity(last.len()),
+        };
+        for (i, (key, property)) in keys.into_iter().zip(children).enumerate() {
+            if last[&key] == i {
+                members.places.insert(key.clone(), members.order.len());
+                members.order.push((key, property));
+            }
+        }
+        members
+    }
+}
+
+// Beyond this many added and removed elements an Array's elements are
+// paired up by index instead
+const MAX_EDITS: usize = 1000;
+
+// The pairs of equal elements along a shortest edit script between `a` and
+// `b` (Myers, "An O(ND) Difference Algorithm"), in O((n + m) * D) time for D
+// edits. None when there are more than MAX_EDITS.
+fn common(a: &[usize], b: &[usize]) -> Option<Vec<(usize, usize)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let limit = (a.len() + b.len()).min(MAX_EDITS) as isize;
+    let offset = limit + 1;
+    // v[offset + k] is the furthest x reached on diagonal k = x - y
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // v as it was before each round, from diagonal -d - 1 to d + 1
+    let mut trace: Vec<Vec<isize>> = vec![];
+    let mut d = 0;
+    'search: loop {
+        if d > limit {
+            return None;
+        }
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let at = |v: &[isize], k: isize| v[(offset + k) as usize];
+            let mut x = if k == -d || (k != d && at(&v, k - 1) < at(&v, k + 1)) {
+                at(&v, k + 1)
+            } else {
+                at(&v, k - 1) + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(offset + k) as usize] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+        d += 1;
+    }
+
+    let mut pairs = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let (d, k) = (d as isize, x - y);
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let previous = if k == -d || (k != d && at(k - 1) < at(k + 1)) { k + 1 } else { k - 1 };
+        let start = at(previous);
+        while x > start && y > start - previous {
+            x -= 1;
+            y -= 1;
+            pairs.push((x as usize, y as usize));
+        }
+        x = start;
+        y = start - previous;
+    }
+    pairs.reverse();
+    Some(pairs)
+}
+
+struct Differ<'a> {
+    options: &'a DiffOptions,
+    changes: Vec<Change>,
+}
+
+impl<'a> Differ<'a> {
+    fn push(&mut self, kind: ChangeKind, pointer: String, old: Option<Span>, new: Option<Span>) {
+        self.changes.push(Change {
+            kind,
+            pointer,
+            old,
+            new,
+        });
+    }
+
+    // Text that is the same for two nodes exactly when diffing them would
+    // find no changes
+    fn fingerprint(&self, node: &Node, out: &mut String) {
+        match *node {
+            Node::Object { ref children, .. } => {
+                let mut entries: Vec<String> = Members::new(children)
+                    .order
+                    .iter()
+                    .map(|&(ref key, property)| {
+                        let mut entry = String::new();
+                        write_string(key, &mut entry);
+                        entry.push(':');
+                        self.fingerprint(&property.value, &mut entry);
+                        entry
+                    })
+                    .collect();
+                if self.options.ignore_key_order {
+                    entries.sort();
+                }
+                out.push('{');
+                out.push_str(&entries.join(","));
+                out.push('}');
+            }
+            Node::Array { ref children, .. } => {
+                out.push('[');
+                for (i, element) in children.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    self.fingerprint(element, out);
+                }
+                out.push(']');
+            }
+            Node::Number { ref raw, .. } => match node.decimal() {
+                Ok(ref value) if self.options.numeric && value.is_zero() => out.push('0'),
+                Ok(ref value) if self.options.numeric => {
+                    if value.negative {
+                        out.push('-');
+                    }
+                    out.push_str(&format!("{}e{}", value.mantissa, value.exponent));
+                }
+                _ => {
+                    out.push('#');
+                    out.push_str(raw);
+                }
+            },
+            Node::String { .. } => write_string(&node.decoded().unwrap_or_default(), out),
+            Node::Boolean { ref raw, .. } | Node::Null { ref raw, .. } => out.push_str(raw),
+        }
+    }
+
+    // Whether diffing the two would find no changes
+    fn equal(&self, old: &Node, new: &Node) -> bool {
+        let (mut a, mut b) = (String::new(), String::new());
+        self.fingerprint(old, &mut a);
+        self.fingerprint(new, &mut b);
+        a == b
+    }
+
+    fn node(&mut self, old: &Node, new: &Node, pointer: String) {
+        match (old, new) {
+            (Node::Object { children: a, .. }, Node::Object { children: b, .. }) => {
+                self.object(old, new, a, b, pointer)
+            }
+            (Node::Array { children: a, .. }, Node::Array { children: b, .. }) => {
+                self.array(a, b, pointer)
+            }
+            _ if old.kind() != new.kind() => {
+                self.push(ChangeKind::TypeChanged, pointer, Some(old.span()), Some(new.span()))
+            }
+            _ if !self.equal(old, new) => {
+                self.push(ChangeKind::Changed, pointer, Some(old.span()), Some(new.span()))
+            }
+            _ => {}
+        }
+    }
+
+    // Removed members are reported just before the first member after them
+    // that is in both Objects, or at the end
+    fn object(&mut self, old: &Node, new: &Node, a: &[Property], b: &[Property], pointer: String) {
+        let (a, b) = (Members::new(a), Members::new(b));
+        if !self.options.ignore_key_order {
+            // The order of the keys in both, ignoring added and removed ones
+            let common = |a: &Members, b: &Members| -> Vec<String> {
+                a.order
+                    .iter()
+                    .filter(|member| b.places.contains_key(&member.0))
+                    .map(|member| member.0.to_string())
+                    .collect()
+            };
+            if common(&a, &b) != common(&b, &a) {
+                self.push(ChangeKind::Reordered, pointer.clone(), Some(old.span()), Some(new.span()));
+            }
+        }
+
+        let mut removed = 0;
+        for &(ref key, property) in &b.order {
+            let place = match a.places.get(key) {
+                Some(&place) => place,
+                None => {
+                    self.push(ChangeKind::Added, child(&pointer, key), None, Some(property.span));
+                    continue;
+                }
+            };
+            self.removed(&a, &b, removed..place.max(removed), &pointer);
+            removed = removed.max(place + 1);
+            self.node(&a.order[place].1.value, &property.value, child(&pointer, key));
+        }
+        self.removed(&a, &b, removed..a.order.len(), &pointer);
+    }
+
+    // The members of `a` in `places` that aren't in `b`
+    fn removed(&mut self, a: &Members, b: &Members, places: Range<usize>, pointer: &str) {
+        for &(ref key, property) in &a.order[places] {
+            if !b.places.contains_key(key) {
+                self.push(ChangeKind::Removed, child(pointer, key), Some(property.span), None);
+            }
+        }
+    }
+
+    // Elements are matched up along a shortest edit script of equal
+    // elements, compared through their fingerprints. Unmatched elements
+    // equal to one elsewhere have moved; the rest are paired up in order
+    // between the matched ones and diffed, and any left over were added or
+    // removed.
+    fn array(&mut self, a: &[Node], b: &[Node], pointer: String) {
+        // Equal elements get the same id
+        let mut ids = HashMap::new();
+        let mut id = |node: &Node| {
+            let mut fingerprint = String::new();
+            self.fingerprint(node, &mut fingerprint);
+            let next = ids.len();
+            *ids.entry(fingerprint).or_insert(next)
+        };
+        let old_ids: Vec<usize> = a.iter().map(&mut id).collect();
+        let new_ids: Vec<usize> = b.iter().map(&mut id).collect();
+
+        let prefix = old_ids.iter().zip(&new_ids).take_while(|&(a, b)| a == b).count();
+        let suffix = old_ids[prefix..]
+            .iter()
+            .rev()
+            .zip(new_ids[prefix..].iter().rev())
+            .take_while(|&(a, b)| a == b)
+            .count();
+        let (n, m) = (a.len() - prefix - suffix, b.len() - prefix - suffix);
+
+        let mut matched: Vec<(usize, usize)> =
+            common(&old_ids[prefix..prefix + n], &new_ids[prefix..prefix + m])
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(i, j)| (prefix + i, prefix + j))
+                .collect();
+        let mut old_used = vec![false; a.len()];
+        let mut new_used = vec![false; b.len()];
+        for &(i, j) in &matched {
+            old_used[i] = true;
+            new_used[j] = true;
+        }
+        matched.push((prefix + n, prefix + m));
+
+        // The unmatched old elements by id, last first
+        let mut unmatched: HashMap<usize, Vec<usize>> = HashMap::new();
+        for i in (prefix..prefix + n).rev().filter(|&i| !old_used[i]) {
+            unmatched.entry(old_ids[i]).or_default().push(i);
+        }
+        let mut moved_from = vec![None; b.len()];
+        for j in (prefix..prefix + m).filter(|&j| !new_used[j]) {
+            if let Some(i) = unmatched.get_mut(&new_ids[j]).and_then(Vec::pop) {
+                old_used[i] = true;
+                moved_from[j] = Some(i);
+            }
+        }
+        let (mut old_start, mut new_start) = (prefix, prefix);
+        for &(old_end, new_end) in &matched {
+            let mut removed = (old_start..old_end).filter(|&i| !old_used[i]);
+            for j in new_start..new_end {
+                let element = child(&pointer, &j.to_string());
+                match moved_from[j] {
+                    Some(i) => self.push(
+                        ChangeKind::Moved {
+                            from: child(&pointer, &i.to_string()),
+                        },
+                        element,
+                        Some(a[i].span()),
+                        Some(b[j].span()),
+                    ),
+                    None => match removed.next() {
+                        Some(i) => self.node(&a[i], &b[j], element),
+                        None => self.push(ChangeKind::Added, element, None, Some(b[j].span())),
+                    },
+                }
+            }
+            for i in removed {
+                let element = child(&pointer, &i.to_string());
+                self.push(ChangeKind::Removed, element, Some(a[i].span()), None);
+            }
+            old_start = old_end + 1;
+            new_start = new_end + 1;
+        }
+    }
+}
+
+// Compares two trees, returning their differences in document order.
+// Removed members and elements come where they were in the old document.
+pub fn diff(old: &Node, new: &Node) -> Vec<Change> {
+    diff_with(old, new, &DiffOptions::default())
+}
+
+pub fn diff_with(old: &Node, new: &Node, options: &DiffOptions) -> Vec<Change> {
+    let mut differ = Differ {
+        options,
+        changes: vec![],
+    };
+    differ.node(old, new, String::new());
+    differ.changes
+}
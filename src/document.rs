@@ -0,0 +1,618 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::ops::Range;
+
+use lines::{CompactSpan, LineTable, OffsetTooLarge};
+use strings;
+use tokenize::Tokens;
+use tree::NodeId;
+use types::{ArrayError, ArrayStatus, Identifier, Node, ParseError, ParseErrorType, Position, Property,
+            PropertyStatus, Span, StringStatus, Token, TokenType};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Object,
+    Array,
+    String,
+    Number,
+    Boolean,
+    Null,
+}
+
+// An Object member. The value is stored in the document like any other node.
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub status: PropertyStatus,
+    pub key_status: StringStatus,
+    // The key as written, without its quotes
+    pub key_raw: Box<str>,
+    pub key_span: CompactSpan,
+    pub value: NodeId,
+    pub span: CompactSpan,
+}
+
+impl Member {
+    // The unescaped key
+    pub fn key(&self) -> Cow<'_, str> {
+        strings::decode(&self.key_raw)
+    }
+
+    pub fn matches(&self, key: &str) -> bool {
+        self.key() == key
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Data {
+    // Ranges into `Document::members` and `Document::elements`
+    Object { members: Range<usize> },
+    Array { status: ArrayStatus, elements: Range<usize> },
+    String { status: StringStatus, raw: Box<str> },
+    Number { raw: Box<str> },
+    Boolean(bool),
+    Null,
+}
+
+// Marks the root's parent
+const NO_PARENT: u32 = u32::MAX;
+
+#[derive(Clone, Debug)]
+struct Slot {
+    data: Data,
+    span: CompactSpan,
+    parent: u32,
+}
+
+// The same information as a `Node` tree, stored in three flat vectors
+// instead of one allocation per container. Nodes are addressed by NodeId,
+// numbered in document order exactly as a TreeIndex over the same tree
+// would, so ids can be kept in diagnostics and passed between the two.
+//
+// Spans are kept as byte offsets and expanded to full positions through a
+// line table on request, which makes a document a fraction of the size of
+// the tree it was built from. This is the low-memory form of a parsed
+// document; `Node` and its spans are unchanged, with a full `Position` at
+// each end. Documents are limited to 4GiB: building one from a larger tree
+// fails with OffsetTooLarge, and parsing one with ParseErrorType::TooLarge.
+#[derive(Clone, Debug)]
+pub struct Document {
+    nodes: Vec<Slot>,
+    members: Vec<Member>,
+    elements: Vec<NodeId>,
+    lines: LineTable,
+}
+
+impl Document {
+    fn empty() -> Document {
+        Document {
+            nodes: vec![],
+            members: vec![],
+            elements: vec![],
+            lines: LineTable::default(),
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        self.members.shrink_to_fit();
+        self.elements.shrink_to_fit();
+    }
+
+    pub fn new(root: &Node) -> Result<Document, OffsetTooLarge> {
+        let mut document = Document::empty();
+        let mut positions = vec![];
+        document.add(root, NO_PARENT, &mut positions)?;
+        document.lines = LineTable::new(positions);
+        document.shrink_to_fit();
+        Ok(document)
+    }
+
+    // Parses `input` straight into a Document, without building the Node
+    // tree or holding on to its tokens, so the memory needed is little more
+    // than the Document itself. Comments are skipped as with `parse_jsonc`,
+    // and missing or trailing commas and invalid strings are recorded in the
+    // same statuses `parse` gives them; any other error stops the parse.
+    pub fn parse(input: &str) -> Result<Document, ParseError> {
+        let mut builder = Builder {
+            tokens: Tokens::new(input),
+            peeked: None,
+            end: Position {
+                line: 1,
+                column: 1,
+                index: 0,
+            },
+            invalid: None,
+            document: Document::empty(),
+        };
+        if u32::try_from(input.len()).is_err() {
+            return Err(builder.error(ParseErrorType::TooLarge));
+        }
+        builder.value(NO_PARENT)?;
+        if builder.peek().is_some() || builder.invalid.is_some() {
+            return Err(builder.error(ParseErrorType::UnexpectedToken));
+        }
+        let mut document = builder.document;
+        document.shrink_to_fit();
+        Ok(document)
+    }
+
+    fn add(
+        &mut self,
+        node: &Node,
+        parent: u32,
+        positions: &mut Vec<Position>,
+    ) -> Result<NodeId, OffsetTooLarge> {
+        let id = NodeId(self.nodes.len());
+        let span = node.span();
+        positions.push(span.start);
+        positions.push(span.end);
+        // Reserve the slot so children are numbered after their parent, then
+        // fill it in once their ranges are known
+        self.nodes.push(Slot {
+            data: Data::Null,
+            span: CompactSpan::try_from(span)?,
+            parent,
+        });
+
+        let data = match *node {
+            Node::Object { ref children, .. } => {
+                let mut members = Vec::with_capacity(children.len());
+                for property in children {
+                    positions.extend(&[property.span.start, property.span.end]);
+                    positions.extend(&[property.key.span.start, property.key.span.end]);
+                    members.push(Member {
+                        status: property.status.clone(),
+                        key_status: property.key.status.clone(),
+                        key_raw: property.key.raw.clone().into_boxed_str(),
+                        key_span: CompactSpan::try_from(property.key.span)?,
+                        value: self.add(&property.value, id.0 as u32, positions)?,
+                        span: CompactSpan::try_from(property.span)?,
+                    });
+                }
+                let start = self.members.len();
+                self.members.extend(members);
+                Data::Object {
+                    members: start..self.members.len(),
+                }
+            }
+            Node::Array {
+                ref status,
+                ref children,
+                ..
+            } => {
+                let mut elements = Vec::with_capacity(children.len());
+                for child in children {
+                    elements.push(self.add(child, id.0 as u32, positions)?);
+                }
+                let start = self.elements.len();
+                self.elements.extend(elements);
+                Data::Array {
+                    status: status.clone(),
+                    elements: start..self.elements.len(),
+                }
+            }
+            Node::String {
+                ref status,
+                ref raw,
+                ..
+            } => Data::String {
+                status: status.clone(),
+                raw: raw.clone().into_boxed_str(),
+            },
+            Node::Number { ref raw, .. } => Data::Number {
+                raw: raw.clone().into_boxed_str(),
+            },
+            Node::Boolean { ref raw, .. } => Data::Boolean(raw == "true"),
+            Node::Null { .. } => Data::Null,
+        };
+        self.nodes[id.0].data = data;
+        Ok(id)
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Every node in document order
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    pub fn kind(&self, id: NodeId) -> NodeKind {
+        match self.nodes[id.0].data {
+            Data::Object { .. } => NodeKind::Object,
+            Data::Array { .. } => NodeKind::Array,
+            Data::String { .. } => NodeKind::String,
+            Data::Number { .. } => NodeKind::Number,
+            Data::Boolean(_) => NodeKind::Boolean,
+            Data::Null => NodeKind::Null,
+        }
+    }
+
+    pub fn span(&self, id: NodeId) -> Span {
+        self.lines.span(self.nodes[id.0].span)
+    }
+
+    pub fn compact_span(&self, id: NodeId) -> CompactSpan {
+        self.nodes[id.0].span
+    }
+
+    // The full positions of a span taken from this document
+    pub fn expand(&self, span: CompactSpan) -> Span {
+        self.lines.span(span)
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        match self.nodes[id.0].parent {
+            NO_PARENT => None,
+            parent => Some(NodeId(parent as usize)),
+        }
+    }
+
+    // The source text of a scalar, None for containers
+    pub fn raw(&self, id: NodeId) -> Option<&str> {
+        match self.nodes[id.0].data {
+            Data::String { ref raw, .. } | Data::Number { ref raw } => Some(raw),
+            Data::Boolean(true) => Some("true"),
+            Data::Boolean(false) => Some("false"),
+            Data::Null => Some("null"),
+            Data::Object { .. } | Data::Array { .. } => None,
+        }
+    }
+
+    // The unescaped value of a String
+    pub fn decoded(&self, id: NodeId) -> Option<Cow<'_, str>> {
+        match self.nodes[id.0].data {
+            Data::String { ref raw, .. } => Some(strings::decode(raw)),
+            _ => None,
+        }
+    }
+
+    pub fn string_status(&self, id: NodeId) -> Option<&StringStatus> {
+        match self.nodes[id.0].data {
+            Data::String { ref status, .. } => Some(status),
+            _ => None,
+        }
+    }
+
+    pub fn array_status(&self, id: NodeId) -> Option<&ArrayStatus> {
+        match self.nodes[id.0].data {
+            Data::Array { ref status, .. } => Some(status),
+            _ => None,
+        }
+    }
+
+    // The members of an Object, empty for anything else
+    pub fn members(&self, id: NodeId) -> &[Member] {
+        match self.nodes[id.0].data {
+            Data::Object { ref members } => &self.members[members.clone()],
+            _ => &[],
+        }
+    }
+
+    // The elements of an Array, empty for anything else
+    pub fn elements(&self, id: NodeId) -> &[NodeId] {
+        match self.nodes[id.0].data {
+            Data::Array { ref elements, .. } => &self.elements[elements.clone()],
+            _ => &[],
+        }
+    }
+
+    // Looks up an Object member by its unescaped key; the last duplicate
+    // wins, as with `Node::property`
+    pub fn get(&self, id: NodeId, key: &str) -> Option<NodeId> {
+        self.members(id)
+            .iter()
+            .rev()
+            .find(|member| member.matches(key))
+            .map(|member| member.value)
+    }
+
+    pub fn get_index(&self, id: NodeId, index: usize) -> Option<NodeId> {
+        self.elements(id).get(index).cloned()
+    }
+
+    // The innermost node whose span contains byte `offset`
+    pub fn id_at(&self, offset: usize) -> Option<NodeId> {
+        if self.nodes.is_empty() || !self.compact_span(self.root()).contains(offset) {
+            return None;
+        }
+        let mut current = self.root();
+        loop {
+            let next = match self.kind(current) {
+                NodeKind::Object => self
+                    .members(current)
+                    .iter()
+                    .map(|member| member.value)
+                    .find(|&value| self.compact_span(value).contains(offset)),
+                NodeKind::Array => self
+                    .elements(current)
+                    .iter()
+                    .cloned()
+                    .find(|&element| self.compact_span(element).contains(offset)),
+                _ => None,
+            };
+            match next {
+                Some(next) => current = next,
+                None => return Some(current),
+            }
+        }
+    }
+
+    // Rebuilds the `Node` tree rooted at `id`
+    pub fn to_node(&self, id: NodeId) -> Node {
+        let slot = &self.nodes[id.0];
+        let span = self.span(id);
+        match slot.data {
+            Data::Object { .. } => Node::Object {
+                children: self
+                    .members(id)
+                    .iter()
+                    .map(|member| Property {
+                        status: member.status.clone(),
+                        key: Identifier {
+                            status: member.key_status.clone(),
+                            raw: member.key_raw.to_string(),
+                            span: self.expand(member.key_span),
+                        },
+                        value: self.to_node(member.value),
+                        span: self.expand(member.span),
+                    })
+                    .collect(),
+                span,
+            },
+            Data::Array { ref status, .. } => Node::Array {
+                status: status.clone(),
+                children: self
+                    .elements(id)
+                    .iter()
+                    .map(|&element| self.to_node(element))
+                    .collect(),
+                span,
+            },
+            Data::String {
+                ref status,
+                ref raw,
+            } => Node::String {
+                status: status.clone(),
+                raw: raw.to_string(),
+                span,
+            },
+            Data::Number { ref raw } => Node::Number {
+                raw: raw.to_string(),
+                span,
+            },
+            Data::Boolean(value) => Node::Boolean {
+                raw: value.to_string(),
+                span,
+            },
+            Data::Null => Node::Null {
+                raw: "null".to_string(),
+                span,
+            },
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Node> for Document {
+    type Error = OffsetTooLarge;
+
+    fn try_from(node: &'a Node) -> Result<Document, OffsetTooLarge> {
+        Document::new(node)
+    }
+}
+
+// Reads a document token by token for `Document::parse`, recording the
+// position of every token in the line table as it goes
+struct Builder<'a> {
+    tokens: Tokens<'a>,
+    peeked: Option<Token>,
+    // The end of the last token read
+    end: Position,
+    // Invalid text, which ends the tokens
+    invalid: Option<ParseError>,
+    document: Document,
+}
+
+impl<'a> Builder<'a> {
+    fn peek(&mut self) -> Option<&Token> {
+        if self.peeked.is_none() {
+            for token in self.tokens.by_ref() {
+                match token {
+                    Ok(ref token)
+                        if matches!(token.kind, TokenType::LineComment | TokenType::BlockComment) => {}
+                    Ok(token) => {
+                        self.peeked = Some(token);
+                        break;
+                    }
+                    Err(error) => self.invalid = Some(error),
+                }
+            }
+            if let Some(ref token) = self.peeked {
+                self.document.lines.push(token.span.start);
+                self.document.lines.push(token.span.end);
+            }
+        }
+        self.peeked.as_ref()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        self.peek();
+        let token = self.peeked.take();
+        if let Some(ref token) = token {
+            self.end = token.span.end;
+        }
+        token
+    }
+
+    fn peek_kind(&mut self) -> Option<TokenType> {
+        self.peek().map(|token| token.kind)
+    }
+
+    // An error at the next token, or at the end of the input. Running into
+    // invalid text is always the error.
+    fn error(&mut self, error_type: ParseErrorType) -> ParseError {
+        if let Some(ref error) = self.invalid {
+            return error.clone();
+        }
+        let end = self.end;
+        let position = self.peek().map_or(end, |token| token.span.start);
+        ParseError {
+            position,
+            error_type,
+        }
+    }
+
+    fn expect(&mut self, kind: TokenType) -> Result<Token, ParseError> {
+        match self.peek_kind() {
+            Some(next) if next == kind => Ok(self.next().unwrap()),
+            Some(_) => Err(self.error(ParseErrorType::UnexpectedToken)),
+            None => Err(self.error(ParseErrorType::UnexpectedEnd)),
+        }
+    }
+
+    fn value(&mut self, parent: u32) -> Result<NodeId, ParseError> {
+        let token = match self.next() {
+            Some(token) => token,
+            None => return Err(self.error(ParseErrorType::UnexpectedEnd)),
+        };
+        let id = NodeId(self.document.nodes.len());
+        let start = token.span.start.index as u32;
+        self.document.nodes.push(Slot {
+            data: Data::Null,
+            span: CompactSpan { start, end: start },
+            parent,
+        });
+
+        let data = match token.kind {
+            TokenType::LeftBrace => self.object(id)?,
+            TokenType::LeftBracket => self.array(id)?,
+            TokenType::String => {
+                let raw = token.value.unwrap_or_default();
+                Data::String {
+                    status: strings::validate(&raw, token.span.start),
+                    raw: raw.into_boxed_str(),
+                }
+            }
+            TokenType::Number => Data::Number {
+                raw: token.value.unwrap_or_default().into_boxed_str(),
+            },
+            TokenType::True => Data::Boolean(true),
+            TokenType::False => Data::Boolean(false),
+            TokenType::Null => Data::Null,
+            _ => {
+                return Err(ParseError {
+                    position: token.span.start,
+                    error_type: ParseErrorType::UnexpectedToken,
+                })
+            }
+        };
+        let slot = &mut self.document.nodes[id.0];
+        slot.data = data;
+        slot.span.end = self.end.index as u32;
+        Ok(id)
+    }
+
+    fn object(&mut self, id: NodeId) -> Result<Data, ParseError> {
+        let mut members: Vec<Member> = vec![];
+        if self.peek_kind() != Some(TokenType::RightBrace) {
+            loop {
+                let key = self.expect(TokenType::String)?;
+                self.expect(TokenType::Colon)?;
+                let value = self.value(id.0 as u32)?;
+                let raw = key.value.unwrap_or_default();
+                let start = key.span.start.index as u32;
+                members.push(Member {
+                    status: PropertyStatus::Valid,
+                    key_status: strings::validate(&raw, key.span.start),
+                    key_raw: raw.into_boxed_str(),
+                    key_span: CompactSpan {
+                        start,
+                        end: key.span.end.index as u32,
+                    },
+                    value,
+                    span: CompactSpan {
+                        start,
+                        end: self.end.index as u32,
+                    },
+                });
+
+                match self.peek_kind() {
+                    Some(TokenType::Comma) => {
+                        self.next();
+                        if self.peek_kind() == Some(TokenType::RightBrace) {
+                            members.last_mut().unwrap().status = PropertyStatus::TrailingComma;
+                            break;
+                        }
+                    }
+                    Some(TokenType::RightBrace) => break,
+                    Some(TokenType::String) => {
+                        members.last_mut().unwrap().status = PropertyStatus::MissingComma;
+                    }
+                    Some(_) => return Err(self.error(ParseErrorType::UnexpectedToken)),
+                    None => return Err(self.error(ParseErrorType::UnexpectedEnd)),
+                }
+            }
+        }
+        self.expect(TokenType::RightBrace)?;
+
+        let start = self.document.members.len();
+        self.document.members.extend(members);
+        Ok(Data::Object {
+            members: start..self.document.members.len(),
+        })
+    }
+
+    fn array(&mut self, id: NodeId) -> Result<Data, ParseError> {
+        let mut elements = vec![];
+        let mut errors = vec![];
+        let mut trailing_comma = false;
+        if self.peek_kind() != Some(TokenType::RightBracket) {
+            loop {
+                elements.push(self.value(id.0 as u32)?);
+                match self.peek_kind() {
+                    Some(TokenType::Comma) => {
+                        self.next();
+                        if self.peek_kind() == Some(TokenType::RightBracket) {
+                            trailing_comma = true;
+                            break;
+                        }
+                    }
+                    Some(TokenType::RightBracket) => break,
+                    Some(TokenType::LeftBrace)
+                    | Some(TokenType::LeftBracket)
+                    | Some(TokenType::String)
+                    | Some(TokenType::Number)
+                    | Some(TokenType::True)
+                    | Some(TokenType::False)
+                    | Some(TokenType::Null) => errors.push(ArrayError::MissingComma(self.end)),
+                    Some(_) => return Err(self.error(ParseErrorType::UnexpectedToken)),
+                    None => return Err(self.error(ParseErrorType::UnexpectedEnd)),
+                }
+            }
+        }
+        let close = self.expect(TokenType::RightBracket)?;
+        if trailing_comma {
+            errors.push(ArrayError::TrailingComma(close.span.end));
+        }
+
+        let start = self.document.elements.len();
+        self.document.elements.extend(elements);
+        Ok(Data::Array {
+            status: if errors.is_empty() {
+                ArrayStatus::Valid
+            } else {
+                ArrayStatus::Invalid(errors)
+            },
+            elements: start..self.document.elements.len(),
+        })
+    }
+}
@@ -0,0 +1,473 @@
+use canonical::write_string;
+use format::{member_spans, FormatOptions, Indent, LineEnding, Printer};
+use pointer;
+use tokenize::tokenize;
+use types::{Node, Property, Span, Token, TokenType};
+
+// A replacement of the bytes `start..end` of a document
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(start: usize, end: usize, new_text: &str) -> TextEdit {
+        TextEdit {
+            start,
+            end,
+            new_text: new_text.to_string(),
+        }
+    }
+}
+
+// Applies edits that don't overlap, given in any order
+pub fn apply_edits(src: &str, edits: &[TextEdit]) -> Result<String, EditError> {
+    let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+    sorted.sort_by_key(|edit| (edit.start, edit.end));
+
+    let mut out = String::with_capacity(src.len());
+    let mut cursor = 0;
+    for edit in sorted {
+        if edit.start < cursor {
+            return Err(EditError::Overlapping);
+        }
+        src.get(edit.start..edit.end).ok_or(EditError::OutOfRange)?;
+        out.push_str(&src[cursor..edit.start]);
+        out.push_str(&edit.new_text);
+        cursor = edit.end;
+    }
+    out.push_str(&src[cursor..]);
+    Ok(out)
+}
+
+// Why `edit::set`, `edit::insert`, `edit::remove` or `apply_edits` couldn't
+// make an edit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EditError {
+    InvalidPointer,
+    // Nothing to edit at the pointer: its parent is missing or is a scalar,
+    // or the index is past the end of an Array
+    NotFound,
+    // The root can be replaced but not removed
+    RemoveRoot,
+    // The text isn't what the tree was parsed from
+    SourceMismatch,
+    // Edits that overlap each other
+    Overlapping,
+    // An edit that ends past the text, or starts or ends inside a character
+    OutOfRange,
+}
+
+// Whether `src` is the text `node` was parsed from, as far as its spans and
+// lexemes tell, so that the spans can be used to edit it
+pub(crate) fn matches_source(src: &str, node: &Node) -> bool {
+    let text = |span: Span| src.get(span.start.index..span.end.index);
+    let quoted = |span: Span, raw: &str| {
+        text(span).is_some_and(|text| {
+            text.len() == raw.len() + 2
+                && text.starts_with('"')
+                && text[1..].starts_with(raw)
+                && text.ends_with('"')
+        })
+    };
+    let bracketed = |span: Span, open: char, close: char| {
+        text(span).is_some_and(|text| text.starts_with(open) && text.ends_with(close))
+    };
+    match *node {
+        Node::Object { ref children, span } => {
+            bracketed(span, '{', '}')
+                && children.iter().all(|property| {
+                    text(property.span).is_some()
+                        && quoted(property.key.span, &property.key.raw)
+                        && matches_source(src, &property.value)
+                })
+        }
+        Node::Array { ref children, span, .. } => {
+            bracketed(span, '[', ']') && children.iter().all(|child| matches_source(src, child))
+        }
+        Node::String { ref raw, span, .. } => quoted(span, raw),
+        Node::Number { ref raw, span }
+        | Node::Boolean { ref raw, span }
+        | Node::Null { ref raw, span } => text(span) == Some(raw),
+    }
+}
+
+fn is_comment(token: &Token) -> bool {
+    matches!(token.kind, TokenType::LineComment | TokenType::BlockComment)
+}
+
+fn blank(text: &str) -> bool {
+    text.trim().is_empty()
+}
+
+fn line_start(src: &str, offset: usize) -> usize {
+    src[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+// The offset just past the newline ending the line `offset` is on
+fn line_end(src: &str, offset: usize) -> usize {
+    src[offset..].find('\n').map_or(src.len(), |i| offset + i + 1)
+}
+
+fn indentation(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches(&[' ', '\t'][..]).len()]
+}
+
+// The indentation step used in `src`, taken from its least indented line
+fn detect_indent(src: &str) -> Indent {
+    let mut spaces = None;
+    for line in src.lines().skip(1) {
+        let indent = indentation(line);
+        let text = &line[indent.len()..];
+        // Skip blank lines and the ` * ` lines of block comments
+        if indent.is_empty() || blank(text) || text.starts_with('*') {
+            continue;
+        }
+        if indent.starts_with('\t') {
+            return Indent::Tabs;
+        }
+        spaces = Some(spaces.map_or(indent.len(), |n: usize| n.min(indent.len())));
+    }
+    Indent::Spaces(spaces.unwrap_or(2))
+}
+
+// An Object member or Array element with the text that belongs to it:
+// comments on lines of their own directly before it, and its comma and any
+// comments after it on the same line
+pub(crate) struct Extent {
+    pub start: usize,
+    pub end: usize,
+    pub comma: Option<(usize, usize)>,
+}
+
+// The text being edited, with its tokens and the layout new values are
+// printed with
+pub(crate) struct Source<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    options: FormatOptions,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(src: &'a str) -> Source<'a> {
+        let line_ending = if src.contains("\r\n") {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        };
+        Source {
+            src,
+            // `src` parsed into the tree being edited, so has no invalid
+            // text to stop the tokens early
+            tokens: tokenize(src).0,
+            options: FormatOptions {
+                indent: detect_indent(src),
+                line_ending,
+                ..FormatOptions::default()
+            },
+        }
+    }
+
+    fn newline(&self) -> &'static str {
+        self.options.line_ending.as_str()
+    }
+
+    // The text on the line of `offset` before it
+    fn line_before(&self, offset: usize) -> &'a str {
+        &self.src[line_start(self.src, offset)..offset]
+    }
+
+    // Whether `offset` is the first thing on its line
+    fn starts_line(&self, offset: usize) -> bool {
+        blank(self.line_before(offset))
+    }
+
+    // The indentation for a new line inside the container at `span`
+    fn inner_indent(&self, span: Span) -> String {
+        let outer = indentation(self.line_before(span.start.index));
+        match self.options.indent {
+            Indent::Spaces(n) => format!("{}{}", outer, " ".repeat(n)),
+            Indent::Tabs => format!("{}\t", outer),
+        }
+    }
+
+    // Prints a member, or an element when there is no key, to follow `line`
+    // on the line it starts on
+    fn print(&self, key: Option<&str>, value: &Node, line: &str) -> String {
+        let mut printer = Printer::new(&self.options, &[]);
+        let column = printer.columns(line);
+        printer.continue_line(indentation(line), column);
+        if let Some(key) = key {
+            let mut quoted = String::new();
+            write_string(key, &mut quoted);
+            printer.push(&quoted);
+            printer.push(": ");
+        }
+        printer.node(value, 0);
+        printer.out
+    }
+
+    pub fn extent(&self, span: Span) -> Extent {
+        let tokens = &self.tokens;
+        let first = tokens.partition_point(|token| token.span.end.index <= span.start.index);
+        let mut i = first;
+        while i > 0 && is_comment(&tokens[i - 1]) {
+            i -= 1;
+        }
+        // Comments on the line of the token before belong to that token
+        let previous_line = if i > 0 { tokens[i - 1].span.end.line } else { 0 };
+        let start = tokens[i..first]
+            .iter()
+            .find(|token| token.span.start.line > previous_line)
+            .map_or(span.start.index, |token| token.span.start.index);
+
+        let mut end = span.end.index;
+        let mut line = span.end.line;
+        let mut comma = None;
+        let after = tokens.partition_point(|token| token.span.start.index < span.end.index);
+        for token in &tokens[after..] {
+            match token.kind {
+                TokenType::Comma if comma.is_none() => {
+                    comma = Some((token.span.start.index, token.span.end.index))
+                }
+                TokenType::LineComment | TokenType::BlockComment if token.span.start.line == line => {}
+                _ => break,
+            }
+            end = token.span.end.index;
+            line = token.span.end.line;
+        }
+        Extent { start, end, comma }
+    }
+
+    // Whether there are comments inside `span` but outside `extent`
+    fn other_comments(&self, span: Span, extent: &Extent) -> bool {
+        self.tokens.iter().any(|token| {
+            let (start, end) = (token.span.start.index, token.span.end.index);
+            is_comment(token)
+                && span.start.index < start
+                && end < span.end.index
+                && !(extent.start <= start && end <= extent.end)
+        })
+    }
+
+    fn replace(&self, old: &Node, value: &Node) -> Vec<TextEdit> {
+        let span = old.span();
+        let text = self.print(None, value, self.line_before(span.start.index));
+        if self.src[span.start.index..span.end.index] == text {
+            vec![]
+        } else {
+            vec![TextEdit {
+                start: span.start.index,
+                end: span.end.index,
+                new_text: text,
+            }]
+        }
+    }
+
+    // Adds a member to an Object, or an element before `index` of an Array
+    fn add(&self, container: &Node, index: usize, key: Option<&str>, value: &Node) -> Vec<TextEdit> {
+        let span = container.span();
+        let spans = member_spans(container);
+
+        if spans.is_empty() {
+            let (open, close) = (span.start.index + 1, span.end.index - 1);
+            // Arrays start out on one line, Objects with a member per line
+            if key.is_none() && blank(&self.src[open..close]) {
+                let text = self.print(key, value, &self.src[line_start(self.src, open)..open]);
+                return vec![TextEdit::new(open, close, &text)];
+            }
+            let indent = self.inner_indent(span);
+            let text = self.print(key, value, &indent);
+            let outer = indentation(self.line_before(span.start.index));
+            return if blank(&self.src[open..close]) {
+                let new_text = format!("{nl}{}{}{nl}{}", indent, text, outer, nl = self.newline());
+                vec![TextEdit::new(open, close, &new_text)]
+            } else if self.starts_line(close) {
+                // Keep comments inside the container before the new member
+                let start = line_start(self.src, close);
+                vec![TextEdit::new(start, start, &format!("{}{}{}", indent, text, self.newline()))]
+            } else {
+                let new_text = format!("{nl}{}{}{nl}{}", indent, text, outer, nl = self.newline());
+                vec![TextEdit::new(close, close, &new_text)]
+            };
+        }
+
+        if index < spans.len() {
+            let next = self.extent(spans[index]);
+            if self.starts_line(next.start) {
+                let start = line_start(self.src, next.start);
+                let indent = indentation(self.line_before(spans[index].start.index));
+                let text = self.print(key, value, indent);
+                let new_text = format!("{}{},{}", indent, text, self.newline());
+                return vec![TextEdit::new(start, start, &new_text)];
+            }
+            let start = spans[index].start.index;
+            let text = self.print(key, value, self.line_before(start));
+            return vec![TextEdit::new(start, start, &format!("{}, ", text))];
+        }
+
+        // After the last member, which gets a comma if it has none
+        let last = spans[spans.len() - 1];
+        let extent = self.extent(last);
+        let comma_end = extent.comma.map_or(last.end.index, |(_, end)| end);
+        let comma = if extent.comma.is_some() { "" } else { "," };
+        if self.starts_line(extent.start) {
+            let indent = indentation(self.line_before(last.start.index));
+            let text = self.print(key, value, indent);
+            let new_text = format!("{}{}{}", self.newline(), indent, text);
+            if comma_end == extent.end {
+                vec![TextEdit::new(comma_end, comma_end, &format!("{}{}", comma, new_text))]
+            } else {
+                vec![
+                    TextEdit::new(last.end.index, last.end.index, comma),
+                    TextEdit::new(extent.end, extent.end, &new_text),
+                ]
+            }
+        } else {
+            let line = format!("{}{} ", self.line_before(comma_end), comma);
+            let text = self.print(key, value, &line);
+            vec![TextEdit::new(comma_end, comma_end, &format!("{} {}", comma, text))]
+        }
+    }
+
+    // Removes an Object member or Array element with its comments and the
+    // comma between it and its neighbours
+    fn remove(&self, container: &Node, index: usize) -> Vec<TextEdit> {
+        let span = container.span();
+        let spans = member_spans(container);
+        let item = self.extent(spans[index]);
+        let rest_of_line = &self.src[item.end..line_end(self.src, item.end)];
+        let own_lines = self.starts_line(item.start) && blank(rest_of_line);
+        let (start, end) = if own_lines {
+            (line_start(self.src, item.start), line_end(self.src, item.end))
+        } else {
+            let rest = &self.src[item.end..];
+            (item.start, self.src.len() - rest.trim_start_matches(&[' ', '\t'][..]).len())
+        };
+
+        if spans.len() == 1 && !self.other_comments(span, &item) {
+            return vec![TextEdit::new(span.start.index + 1, span.end.index - 1, "")];
+        }
+        if index == 0 || index + 1 < spans.len() {
+            return vec![TextEdit::new(start, end, "")];
+        }
+
+        // The last item takes the comma before it with it
+        let previous = self.extent(spans[index - 1]);
+        let (comma_start, comma_end) = match previous.comma {
+            Some(comma) => comma,
+            None => return vec![TextEdit::new(start, end, "")],
+        };
+        let before = &self.src[..item.start];
+        if own_lines {
+            // Along with any blank lines that would be left before the end
+            let start = line_end(self.src, previous.end);
+            vec![TextEdit::new(comma_start, comma_end, ""), TextEdit::new(start, end, "")]
+        } else if before.trim_end().len() == comma_end {
+            vec![TextEdit::new(comma_start, end, "")]
+        } else {
+            let start = before.trim_end_matches(&[' ', '\t'][..]).len();
+            vec![TextEdit::new(comma_start, comma_end, ""), TextEdit::new(start, end, "")]
+        }
+    }
+}
+
+// The last member with the key, as with `Node::get`
+fn key_index(children: &[Property], key: &str) -> Option<usize> {
+    children.iter().rposition(|property| property.key.decoded() == key)
+}
+
+// The container a pointer points into and its last reference token, or None
+// for the root
+fn parent<'a>(root: &'a Node, pointer: &str) -> Result<Option<(&'a Node, String)>, EditError> {
+    let mut tokens = pointer::tokens(pointer).ok_or(EditError::InvalidPointer)?;
+    let last = match tokens.pop() {
+        Some(last) => last,
+        None => return Ok(None),
+    };
+    let mut current = root;
+    for token in &tokens {
+        current = match *current {
+            Node::Object { .. } => current.get(token),
+            Node::Array { .. } => pointer::array_index(token).and_then(|i| current.get_index(i)),
+            _ => None,
+        }
+        .ok_or(EditError::NotFound)?;
+    }
+    Ok(Some((current, last)))
+}
+
+fn write(
+    src: &str,
+    root: &Node,
+    pointer: &str,
+    value: &Node,
+    insert: bool,
+) -> Result<Vec<TextEdit>, EditError> {
+    if !matches_source(src, root) {
+        return Err(EditError::SourceMismatch);
+    }
+    let source = Source::new(src);
+    let (container, token) = match parent(root, pointer)? {
+        Some(parent) => parent,
+        None => return Ok(source.replace(root, value)),
+    };
+    match *container {
+        Node::Object { ref children, .. } => Ok(match key_index(children, &token) {
+            Some(i) => source.replace(&children[i].value, value),
+            None => source.add(container, children.len(), Some(&token), value),
+        }),
+        Node::Array { ref children, .. } => {
+            let index = if token == "-" {
+                children.len()
+            } else {
+                pointer::array_index(&token).ok_or(EditError::NotFound)?
+            };
+            if index < children.len() && !insert {
+                Ok(source.replace(&children[index], value))
+            } else if index <= children.len() {
+                Ok(source.add(container, index, None, value))
+            } else {
+                Err(EditError::NotFound)
+            }
+        }
+        _ => Err(EditError::NotFound),
+    }
+}
+
+// Sets the value at a JSON Pointer in `src`, which `root` was parsed from,
+// returning the edits to make to the text, or SourceMismatch if it wasn't.
+// An existing value is replaced, a missing Object member is added at the
+// end, and `-` or the length of an Array appends to it. Everything else in the text is left alone: new text
+// follows the indentation and line endings around it, and commas and
+// comments are kept in place.
+pub fn set(src: &str, root: &Node, pointer: &str, value: &Node) -> Result<Vec<TextEdit>, EditError> {
+    write(src, root, pointer, value, false)
+}
+
+// Like `set`, except that an Array index inserts before the element there
+// rather than replacing it, as with the JSON Patch `add` operation
+pub fn insert(src: &str, root: &Node, pointer: &str, value: &Node) -> Result<Vec<TextEdit>, EditError> {
+    write(src, root, pointer, value, true)
+}
+
+// Removes the value at a JSON Pointer along with its key, the comma
+// separating it from its neighbours and the comments attached to it.
+// Members or elements left on lines of their own are removed with their
+// lines.
+pub fn remove(src: &str, root: &Node, pointer: &str) -> Result<Vec<TextEdit>, EditError> {
+    if !matches_source(src, root) {
+        return Err(EditError::SourceMismatch);
+    }
+    let source = Source::new(src);
+    let (container, token) = parent(root, pointer)?.ok_or(EditError::RemoveRoot)?;
+    let index = match *container {
+        Node::Object { ref children, .. } => key_index(children, &token),
+        Node::Array { ref children, .. } => pointer::array_index(&token).filter(|&i| i < children.len()),
+        _ => None,
+    };
+    let index = index.ok_or(EditError::NotFound)?;
+    Ok(source.remove(container, index))
+}
@@ -0,0 +1,528 @@
+use std::cmp;
+
+use edit::TextEdit;
+use types::{Comment, CommentKind, Node, Property, Span};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Indent {
+    Spaces(usize),
+    Tabs,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeySpacing {
+    // `"key":value`
+    Tight,
+    // `"key": value`
+    Space,
+    // `"key": value`, with the values of each Object lined up in a column
+    Aligned,
+}
+
+#[derive(Clone, Debug)]
+pub struct FormatOptions {
+    pub indent: Indent,
+    // How many columns a tab counts for against `max_width`
+    pub tab_width: usize,
+    pub line_ending: LineEnding,
+    pub final_newline: bool,
+    // Arrays are kept on one line when they fit in this many columns.
+    // Objects always get one member per line.
+    pub max_width: usize,
+    // Arrays of scalars that don't fit on one line are filled, with as many
+    // elements on each line as fit, rather than getting one per line
+    pub compact_arrays: bool,
+    pub key_spacing: KeySpacing,
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions {
+            indent: Indent::Spaces(2),
+            tab_width: 4,
+            line_ending: LineEnding::Lf,
+            final_newline: true,
+            max_width: 80,
+            compact_arrays: false,
+            key_spacing: KeySpacing::Space,
+        }
+    }
+}
+
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+// Numbers and strings are printed exactly as they were written
+fn scalar(node: &Node) -> Option<String> {
+    match *node {
+        Node::String { ref raw, .. } => Some(format!("\"{}\"", raw)),
+        Node::Number { ref raw, .. } | Node::Boolean { ref raw, .. } | Node::Null { ref raw, .. } => {
+            Some(raw.clone())
+        }
+        Node::Object { .. } | Node::Array { .. } => None,
+    }
+}
+
+// A node on a single line, if it can go on one. Only Objects that are empty
+// can.
+fn inline(node: &Node) -> Option<String> {
+    match *node {
+        Node::Object { ref children, .. } if children.is_empty() => Some("{}".to_string()),
+        Node::Object { .. } => None,
+        Node::Array { ref children, .. } => {
+            let elements: Option<Vec<String>> = children.iter().map(inline).collect();
+            Some(format!("[{}]", elements?.join(", ")))
+        }
+        _ => scalar(node),
+    }
+}
+
+pub struct Printer<'a> {
+    options: &'a FormatOptions,
+    // Sorted by position
+    comments: &'a [Comment],
+    // Whitespace at the start of every line after the first, before the
+    // indentation for its depth
+    prefix: String,
+    pub out: String,
+    // Columns used on the current line
+    column: usize,
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(options: &'a FormatOptions, comments: &'a [Comment]) -> Printer<'a> {
+        Printer {
+            options,
+            comments,
+            prefix: String::new(),
+            out: String::new(),
+            column: 0,
+        }
+    }
+
+    // Prints as if starting at `column` of a line that begins with `prefix`
+    pub fn continue_line(&mut self, prefix: &str, column: usize) {
+        self.prefix = prefix.to_string();
+        self.column = column;
+    }
+
+    pub fn columns(&self, text: &str) -> usize {
+        text.chars()
+            .map(|c| if c == '\t' { self.options.tab_width } else { 1 })
+            .sum()
+    }
+
+    pub fn push(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.column += width(text);
+    }
+
+    pub fn newline(&mut self, depth: usize) {
+        self.out.push_str(self.options.line_ending.as_str());
+        self.out.push_str(&self.prefix);
+        self.column = self.columns(&self.prefix);
+        self.indent(depth);
+    }
+
+    pub fn indent(&mut self, depth: usize) {
+        match self.options.indent {
+            Indent::Spaces(n) => {
+                self.out.extend((0..n * depth).map(|_| ' '));
+                self.column += n * depth;
+            }
+            Indent::Tabs => {
+                self.out.extend((0..depth).map(|_| '\t'));
+                self.column += self.options.tab_width * depth;
+            }
+        }
+    }
+
+    // Prints a comment as written, except that the line breaks inside a
+    // block comment become the ones in the options
+    fn comment(&mut self, comment: &Comment) {
+        let mut lines = comment.text.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            if lines.peek().is_none() {
+                self.push(line);
+                break;
+            }
+            self.out.push_str(line.strip_suffix('\r').unwrap_or(line));
+            self.out.push_str(self.options.line_ending.as_str());
+            self.column = 0;
+        }
+    }
+
+    // The comments starting between byte offsets `start` and `end`
+    fn comments_between(&self, start: usize, end: usize) -> &'a [Comment] {
+        let from = self
+            .comments
+            .partition_point(|comment| comment.span.start.index < start);
+        let to = self
+            .comments
+            .partition_point(|comment| comment.span.start.index < end);
+        &self.comments[from..to]
+    }
+
+    fn has_comments(&self, span: Span) -> bool {
+        !self.comments_between(span.start.index, span.end.index).is_empty()
+    }
+
+    // Prints the comments in `comments` that are on `line`, where the
+    // previous token ended, after it. Returns the rest.
+    fn trailing_comments(&mut self, comments: &'a [Comment], line: usize) -> &'a [Comment] {
+        let count = comments
+            .iter()
+            .take_while(|comment| comment.span.start.line == line)
+            .count();
+        for comment in &comments[..count] {
+            self.push(" ");
+            self.comment(comment);
+        }
+        &comments[count..]
+    }
+
+    // Prints comments that go before a value on the current line. A block
+    // comment that was on the same line as the value stays in front of it,
+    // anything else gets a line of its own.
+    fn leading_comments(&mut self, comments: &[Comment], line: usize, depth: usize) {
+        for comment in comments {
+            self.comment(comment);
+            if comment.kind == CommentKind::Block && comment.span.end.line == line {
+                self.push(" ");
+            } else {
+                self.newline(depth);
+            }
+        }
+    }
+
+    fn key(&mut self, raw: &str, pad: usize) {
+        self.push(&format!("\"{}\"", raw));
+        match self.options.key_spacing {
+            KeySpacing::Tight => self.push(":"),
+            KeySpacing::Space => self.push(": "),
+            KeySpacing::Aligned => {
+                let padding: String = (0..pad).map(|_| ' ').collect();
+                self.push(&format!(":{} ", padding));
+            }
+        }
+    }
+
+    fn property(&mut self, property: &Property, key_width: usize, depth: usize) {
+        self.key(&property.key.raw, key_width - width(&property.key.raw));
+
+        // Comments around the colon go between it and the value, which is
+        // indented a level further when it has to go on the next line
+        let value = &property.value;
+        let comments = self.comments_between(property.key.span.end.index, value.span().start.index);
+        for comment in comments {
+            self.comment(comment);
+            match comment.kind {
+                CommentKind::Block => self.push(" "),
+                CommentKind::Line => self.newline(depth + 1),
+            }
+        }
+        self.node(value, depth);
+    }
+
+    // Prints the members of a container after its opening bracket, one per
+    // line along with the comments around them, then the closing bracket.
+    // `print` prints the member with the given index.
+    fn members<F>(&mut self, span: Span, items: &[Span], depth: usize, close: &str, print: F)
+    where
+        F: FnMut(&mut Printer<'a>, usize),
+    {
+        let comments = self.comments_between(span.start.index + 1, span.end.index - 1);
+        let (comments, line) = self.items(comments, span.start.line, items, depth + 1, true, print);
+        let dangling = self.trailing_comments(comments, line);
+        for comment in dangling {
+            self.newline(depth + 1);
+            self.comment(comment);
+        }
+        self.newline(depth);
+        self.push(close);
+    }
+
+    // Prints members separated by commas, each on a new line (except the
+    // first, when `newline` is false) along with the comments before them.
+    // Returns the comments after the last one, and the line it ended on.
+    fn items<F>(
+        &mut self,
+        mut comments: &'a [Comment],
+        mut line: usize,
+        items: &[Span],
+        depth: usize,
+        newline: bool,
+        mut print: F,
+    ) -> (&'a [Comment], usize)
+    where
+        F: FnMut(&mut Printer<'a>, usize),
+    {
+        for (i, item) in items.iter().enumerate() {
+            let split = comments.partition_point(|comment| comment.span.start.index < item.start.index);
+            // Comments between a comma and a member on the same line go
+            // in front of that member
+            let leading = if i > 0 && item.start.line == line {
+                &comments[..split]
+            } else {
+                self.trailing_comments(&comments[..split], line)
+            };
+            if newline || i > 0 {
+                self.newline(depth);
+            }
+            self.leading_comments(leading, item.start.line, depth);
+            print(self, i);
+            if i + 1 < items.len() {
+                self.push(",");
+            }
+
+            // Comments inside the member were printed with it
+            let rest = &comments[split..];
+            let after = rest.partition_point(|comment| comment.span.start.index < item.end.index);
+            comments = &rest[after..];
+            line = item.end.line;
+        }
+        (comments, line)
+    }
+
+    // Prints `node` starting at the current column, with `depth` being the
+    // indentation level of the line it starts on
+    pub fn node(&mut self, node: &Node, depth: usize) {
+        match *node {
+            Node::Object { ref children, span } => {
+                if children.is_empty() && !self.has_comments(span) {
+                    self.push("{}");
+                    return;
+                }
+
+                let key_width = children
+                    .iter()
+                    .map(|property| width(&property.key.raw))
+                    .max()
+                    .unwrap_or(0);
+                let spans: Vec<Span> = children.iter().map(|property| property.span).collect();
+
+                self.push("{");
+                self.members(span, &spans, depth, "}", |printer, i| {
+                    printer.property(&children[i], key_width, depth + 1)
+                });
+            }
+            Node::Array { ref children, span, .. } => {
+                let has_comments = self.has_comments(span);
+                if children.is_empty() && !has_comments {
+                    self.push("[]");
+                    return;
+                }
+
+                // Leave room for a comma after the array
+                if let Some(text) = inline(node) {
+                    if !has_comments && self.column + width(&text) < self.options.max_width {
+                        self.push(&text);
+                        return;
+                    }
+                }
+
+                let scalars: Option<Vec<String>> = children.iter().map(scalar).collect();
+                match scalars {
+                    Some(ref scalars) if self.options.compact_arrays && !has_comments => {
+                        self.push("[");
+                        self.newline(depth + 1);
+                        for (i, text) in scalars.iter().enumerate() {
+                            if i > 0 {
+                                self.push(",");
+                                if self.column + 1 + width(text) < self.options.max_width {
+                                    self.push(" ");
+                                } else {
+                                    self.newline(depth + 1);
+                                }
+                            }
+                            self.push(text);
+                        }
+                        self.newline(depth);
+                        self.push("]");
+                    }
+                    _ => {
+                        let spans: Vec<Span> = children.iter().map(|child| child.span()).collect();
+                        self.push("[");
+                        self.members(span, &spans, depth, "]", |printer, i| {
+                            printer.node(&children[i], depth + 1)
+                        });
+                    }
+                }
+            }
+            _ => {
+                let text = scalar(node).unwrap();
+                self.push(&text);
+            }
+        }
+    }
+}
+
+// Prints a tree as text. Only whitespace is decided by the options: number
+// and string lexemes are kept exactly as parsed, so formatting never changes
+// a value, and formatting the output again gives the same text.
+pub fn format(node: &Node, options: &FormatOptions) -> String {
+    format_with_comments(node, &[], options)
+}
+
+// Formats a JSONC document, keeping the comments returned by `parse_jsonc`.
+// Comments on the same line as the token before them stay there, unless a
+// member follows them on that line after a comma, when they go in front of
+// it. Others get a line of their own before the next member, or before the
+// closing bracket when nothing follows them.
+pub fn format_with_comments(node: &Node, comments: &[Comment], options: &FormatOptions) -> String {
+    let mut printer = Printer::new(options, comments);
+    let span = node.span();
+
+    let leading = printer.comments_between(0, span.start.index);
+    printer.leading_comments(leading, span.start.line, 0);
+    printer.node(node, 0);
+    let trailing = printer.comments_between(span.end.index, usize::MAX);
+    let rest = printer.trailing_comments(trailing, span.end.line);
+    for comment in rest {
+        printer.newline(0);
+        printer.comment(comment);
+    }
+
+    if options.final_newline {
+        printer.push(options.line_ending.as_str());
+    }
+    printer.out
+}
+
+// What a range format rewrites
+enum Target<'a> {
+    Node(&'a Node),
+    // A run of consecutive members of an Object or Array
+    Members(&'a Node, usize, usize),
+}
+
+pub fn member_spans(node: &Node) -> Vec<Span> {
+    match *node {
+        Node::Object { ref children, .. } => children.iter().map(|property| property.span).collect(),
+        Node::Array { ref children, .. } => children.iter().map(|child| child.span()).collect(),
+        _ => vec![],
+    }
+}
+
+// The smallest complete values covering `start..end`
+fn target(root: &Node, start: usize, end: usize) -> Target<'_> {
+    let within = |span: Span| span.start.index <= start && end <= span.end.index;
+    let mut current = root;
+    loop {
+        let span = current.span();
+        let spans = member_spans(current);
+        // Anything touching the brackets of a container takes all of it
+        if spans.is_empty() || start <= span.start.index || span.end.index <= end {
+            return Target::Node(current);
+        }
+
+        let overlapping: Vec<usize> = (0..spans.len())
+            .filter(|&i| spans[i].start.index < end && start < spans[i].end.index)
+            .collect();
+        let (first, last) = match (overlapping.first(), overlapping.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return Target::Node(current),
+        };
+        if first != last || !within(spans[first]) {
+            return Target::Members(current, first, last);
+        }
+
+        // Inside a single member, which may have smaller values inside it
+        let child = match *current {
+            Node::Object { ref children, .. } => {
+                let value = &children[first].value;
+                if !within(value.span()) {
+                    return Target::Members(current, first, first);
+                }
+                value
+            }
+            Node::Array { ref children, .. } => &children[first],
+            _ => unreachable!(),
+        };
+        current = child;
+    }
+}
+
+// Reformats only the part of `src` around the bytes `start..end`: the
+// smallest run of complete values or members covering it, indented to match
+// the line it starts on. `node` and `comments` come from parsing `src`.
+// Returns no edits when that part is already formatted.
+pub fn format_range(
+    src: &str,
+    node: &Node,
+    comments: &[Comment],
+    start: usize,
+    end: usize,
+    options: &FormatOptions,
+) -> Vec<TextEdit> {
+    // An empty range formats what the cursor is on
+    let end = cmp::max(end, start + 1);
+    let target = target(node, start, end);
+    let (from, to) = match target {
+        Target::Node(node) => (node.span().start.index, node.span().end.index),
+        Target::Members(container, first, last) => {
+            let spans = member_spans(container);
+            (spans[first].start.index, spans[last].end.index)
+        }
+    };
+
+    let mut printer = Printer::new(options, comments);
+    let line_start = src[..from].rfind('\n').map_or(0, |i| i + 1);
+    let line = &src[line_start..from];
+    let indent = line.len() - line.trim_start_matches(&[' ', '\t'][..]).len();
+    let column = printer.columns(line);
+    printer.continue_line(&line[..indent], column);
+
+    match target {
+        Target::Node(node) => printer.node(node, 0),
+        Target::Members(container, first, last) => {
+            let spans = member_spans(container);
+            let comments = printer.comments_between(from, to);
+            let line = spans[first].start.line;
+            match *container {
+                Node::Object { ref children, .. } => {
+                    let key_width = children
+                        .iter()
+                        .map(|property| width(&property.key.raw))
+                        .max()
+                        .unwrap_or(0);
+                    printer.items(comments, line, &spans[first..=last], 0, false, |printer, i| {
+                        printer.property(&children[first + i], key_width, 0)
+                    });
+                }
+                Node::Array { ref children, .. } => {
+                    printer.items(comments, line, &spans[first..=last], 0, false, |printer, i| {
+                        printer.node(&children[first + i], 0)
+                    });
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    if src[from..to] == printer.out {
+        vec![]
+    } else {
+        vec![TextEdit {
+            start: from,
+            end: to,
+            new_text: printer.out,
+        }]
+    }
+}
@@ -0,0 +1,400 @@
+// A small matcher for I-Regexp (RFC 9485), the regular expression dialect
+// used by the JSONPath `match` and `search` functions. Patterns compile to
+// an NFA that is run over the input one character at a time, keeping every
+// live state at once, so matching takes time linear in the input whatever
+// the pattern. Unicode character class escapes (`\p{..}`) are not
+// supported and are treated as invalid expressions, as are patterns whose
+// counted repetitions would compile to more than MAX_PROGRAM instructions.
+
+use std::mem;
+
+const MAX_PROGRAM: usize = 100_000;
+
+enum Re {
+    Char(char),
+    // `.`, which matches anything except line breaks
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Concat(Vec<Re>),
+    Alternation(Vec<Re>),
+    Repeat {
+        re: Box<Re>,
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+enum Inst {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    // Continue at both targets
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+impl Inst {
+    fn accepts(&self, c: char) -> bool {
+        match *self {
+            Inst::Char(expected) => c == expected,
+            Inst::Any => c != '\n' && c != '\r',
+            Inst::Class {
+                ref ranges,
+                negated,
+            } => ranges.iter().any(|&(from, to)| from <= c && c <= to) != negated,
+            _ => false,
+        }
+    }
+}
+
+pub struct Regex {
+    program: Vec<Inst>,
+}
+
+struct Parser<'a> {
+    chars: &'a [char],
+    i: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.i).cloned()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.i += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternation(&mut self) -> Option<Re> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        if branches.len() == 1 {
+            branches.pop()
+        } else {
+            Some(Re::Alternation(branches))
+        }
+    }
+
+    fn concat(&mut self) -> Option<Re> {
+        let mut items = vec![];
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            items.push(self.quantified(atom)?);
+        }
+        Some(Re::Concat(items))
+    }
+
+    fn quantified(&mut self, atom: Re) -> Option<Re> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.i += 1;
+                let min = self.integer()?;
+                let max = if self.eat(',') {
+                    if self.peek() == Some('}') {
+                        None
+                    } else {
+                        Some(self.integer()?)
+                    }
+                } else {
+                    Some(min)
+                };
+                if self.peek() != Some('}') || max.is_some_and(|max| max < min) {
+                    return None;
+                }
+                (min, max)
+            }
+            _ => return Some(atom),
+        };
+        self.i += 1;
+        Some(Re::Repeat {
+            re: Box::new(atom),
+            min,
+            max,
+        })
+    }
+
+    fn integer(&mut self) -> Option<usize> {
+        let start = self.i;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.i += 1;
+        }
+        if start == self.i {
+            return None;
+        }
+        self.chars[start..self.i]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    fn escape(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.i += 1;
+        match c {
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            't' => Some('\t'),
+            '(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\' | ']' | '^' | '{' | '|' | '}' => {
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+
+    fn atom(&mut self) -> Option<Re> {
+        let c = self.peek()?;
+        self.i += 1;
+        match c {
+            '.' => Some(Re::Any),
+            '(' => {
+                let re = self.alternation()?;
+                if self.eat(')') {
+                    Some(re)
+                } else {
+                    None
+                }
+            }
+            '[' => self.class(),
+            '\\' => self.escape().map(Re::Char),
+            '*' | '+' | '?' | '{' | '}' | ']' => None,
+            _ => Some(Re::Char(c)),
+        }
+    }
+
+    fn class_char(&mut self) -> Option<char> {
+        match self.peek()? {
+            '\\' => {
+                self.i += 1;
+                self.escape()
+            }
+            '[' | ']' => None,
+            c => {
+                self.i += 1;
+                Some(c)
+            }
+        }
+    }
+
+    fn class(&mut self) -> Option<Re> {
+        let negated = self.eat('^');
+        let mut ranges = vec![];
+        loop {
+            if self.eat(']') {
+                break;
+            }
+            let from = self.class_char()?;
+            let to = if self.peek() == Some('-') && self.chars.get(self.i + 1) != Some(&']') {
+                self.i += 1;
+                self.class_char()?
+            } else {
+                from
+            };
+            if to < from {
+                return None;
+            }
+            ranges.push((from, to));
+        }
+        Some(Re::Class { ranges, negated })
+    }
+}
+
+// How many instructions `re` compiles to, saturating on overflow
+fn size(re: &Re) -> usize {
+    match *re {
+        Re::Char(_) | Re::Any | Re::Class { .. } => 1,
+        Re::Concat(ref items) => items.iter().fold(0, |total, re| total.saturating_add(size(re))),
+        Re::Alternation(ref branches) => branches
+            .iter()
+            .fold(2 * (branches.len() - 1), |total, re| total.saturating_add(size(re))),
+        Re::Repeat { ref re, min, max } => {
+            let body = size(re);
+            let optional = match max {
+                Some(max) => (max - min).saturating_mul(body + 1),
+                None => body + 2,
+            };
+            min.saturating_mul(body).saturating_add(optional)
+        }
+    }
+}
+
+fn compile(re: &Re, program: &mut Vec<Inst>) {
+    match *re {
+        Re::Char(c) => program.push(Inst::Char(c)),
+        Re::Any => program.push(Inst::Any),
+        Re::Class {
+            ref ranges,
+            negated,
+        } => program.push(Inst::Class {
+            ranges: ranges.clone(),
+            negated,
+        }),
+        Re::Concat(ref items) => {
+            for re in items {
+                compile(re, program);
+            }
+        }
+        Re::Alternation(ref branches) => {
+            let mut jumps = vec![];
+            for (i, re) in branches.iter().enumerate() {
+                if i + 1 == branches.len() {
+                    compile(re, program);
+                    break;
+                }
+                let split = program.len();
+                program.push(Inst::Split(split + 1, 0));
+                compile(re, program);
+                jumps.push(program.len());
+                program.push(Inst::Jump(0));
+                program[split] = Inst::Split(split + 1, program.len());
+            }
+            for jump in jumps {
+                program[jump] = Inst::Jump(program.len());
+            }
+        }
+        Re::Repeat { ref re, min, max } => {
+            for _ in 0..min {
+                compile(re, program);
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(re, program);
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    let mut splits = vec![];
+                    for _ in min..max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(program.len() + 1, 0));
+                        compile(re, program);
+                    }
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, program.len());
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The states the NFA is in, each at most once
+struct Threads {
+    pcs: Vec<usize>,
+    live: Vec<bool>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Threads {
+        Threads {
+            pcs: Vec::with_capacity(len),
+            live: vec![false; len],
+        }
+    }
+
+    fn clear(&mut self) {
+        for &pc in &self.pcs {
+            self.live[pc] = false;
+        }
+        self.pcs.clear();
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Option<Regex> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut parser = Parser { chars: &chars, i: 0 };
+        let re = parser.alternation()?;
+        if parser.i != chars.len() || size(&re) >= MAX_PROGRAM {
+            return None;
+        }
+        let mut program = vec![];
+        compile(&re, &mut program);
+        program.push(Inst::Match);
+        Some(Regex { program })
+    }
+
+    // Adds the state at `pc` to `threads`, following jumps and splits
+    // without consuming anything
+    fn add(&self, threads: &mut Threads, pc: usize) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if threads.live[pc] {
+                continue;
+            }
+            threads.live[pc] = true;
+            threads.pcs.push(pc);
+            match self.program[pc] {
+                Inst::Jump(to) => stack.push(to),
+                Inst::Split(first, second) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Runs the NFA over `input`. A `whole` match has to start at the
+    // beginning and end at the end, otherwise any substring will do.
+    fn run(&self, input: &str, whole: bool) -> bool {
+        let accept = self.program.len() - 1;
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        let mut chars = input.chars();
+        let mut first = true;
+        loop {
+            if first || !whole {
+                self.add(&mut current, 0);
+                first = false;
+            }
+            let c = chars.next();
+            if current.live[accept] && (!whole || c.is_none()) {
+                return true;
+            }
+            let c = match c {
+                Some(c) if !current.pcs.is_empty() || !whole => c,
+                _ => return false,
+            };
+            for &pc in &current.pcs {
+                if self.program[pc].accepts(c) {
+                    self.add(&mut next, pc + 1);
+                }
+            }
+            current.clear();
+            mem::swap(&mut current, &mut next);
+        }
+    }
+
+    // Whether the whole of `input` matches
+    pub fn is_match(&self, input: &str) -> bool {
+        self.run(input, true)
+    }
+
+    // Whether any substring of `input` matches
+    pub fn is_found(&self, input: &str) -> bool {
+        self.run(input, false)
+    }
+}
@@ -0,0 +1,1025 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use iregexp::Regex;
+use pointer::PathSegment;
+use types::{Node, Span};
+
+// Largest index allowed in a query (I-JSON's exact integer range)
+const MAX_INDEX: i64 = (1 << 53) - 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonPathError {
+    // Byte offset into the query
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+// A node selected by a query, and where it is
+#[derive(Clone, Debug)]
+pub struct QueryMatch<'a> {
+    pub node: &'a Node,
+    pub path: Vec<PathSegment>,
+}
+
+impl<'a> QueryMatch<'a> {
+    pub fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    // The normalized path of the node, e.g. `$['servers'][0]`
+    pub fn normalized_path(&self) -> String {
+        normalized_path(&self.path)
+    }
+}
+
+pub fn normalized_path(path: &[PathSegment]) -> String {
+    let mut out = "$".to_string();
+    for segment in path {
+        match *segment {
+            PathSegment::Key(ref key) => {
+                out.push_str("['");
+                for c in key.chars() {
+                    match c {
+                        '\u{8}' => out.push_str("\\b"),
+                        '\u{c}' => out.push_str("\\f"),
+                        '\n' => out.push_str("\\n"),
+                        '\r' => out.push_str("\\r"),
+                        '\t' => out.push_str("\\t"),
+                        '\'' => out.push_str("\\'"),
+                        '\\' => out.push_str("\\\\"),
+                        c if c < '\u{20}' => out.push_str(&format!("\\u{:04x}", c as u32)),
+                        c => out.push(c),
+                    }
+                }
+                out.push_str("']");
+            }
+            PathSegment::Index(index) => out.push_str(&format!("[{}]", index)),
+        }
+    }
+    out
+}
+
+// Query syntax
+
+#[derive(Debug)]
+struct Query {
+    // Starts at `@` rather than `$`
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+impl Query {
+    // Whether the query can select at most one node
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| match *segment {
+            Segment::Child(ref selectors) => {
+                matches!(selectors[..], [Selector::Name(_)] | [Selector::Index(_)])
+            }
+            Segment::Descendant(_) => false,
+        })
+    }
+}
+
+#[derive(Debug)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Literal {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum FunctionName {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+#[derive(Debug)]
+struct Function {
+    name: FunctionName,
+    args: Vec<Comparable>,
+}
+
+#[derive(Debug)]
+enum Comparable {
+    Literal(Literal),
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(Debug)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Exists(Query),
+    Function(Function),
+    Compare(Comparable, Comparison, Comparable),
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    i: usize,
+}
+
+fn is_name_first(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c >= '\u{80}'
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_first(c) || c.is_ascii_digit()
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: &str) -> Result<T, JsonPathError> {
+        Err(JsonPathError {
+            offset: self.i,
+            message: message.to_string(),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.i..].chars().next()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.i..]
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.i += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), JsonPathError> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            self.error(&format!("expected `{}`", s))
+        }
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == ' ' || c == '\t' || c == '\n' || c == '\r' {
+                self.i += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, JsonPathError> {
+        let relative = if self.eat("$") {
+            false
+        } else if self.eat("@") {
+            true
+        } else {
+            return self.error("expected `$` or `@`");
+        };
+
+        let mut segments = vec![];
+        loop {
+            // Whitespace is only allowed before a segment, so put it back if
+            // none follows
+            let before = self.i;
+            self.whitespace();
+            match self.peek() {
+                Some('[') | Some('.') => segments.push(self.segment()?),
+                _ => {
+                    self.i = before;
+                    break;
+                }
+            }
+        }
+
+        Ok(Query { relative, segments })
+    }
+
+    fn segment(&mut self) -> Result<Segment, JsonPathError> {
+        if self.eat("..") {
+            let selectors = match self.peek() {
+                Some('[') => self.bracketed()?,
+                Some('*') => {
+                    self.i += 1;
+                    vec![Selector::Wildcard]
+                }
+                Some(c) if is_name_first(c) => vec![Selector::Name(self.member_name())],
+                _ => return self.error("expected a selector after `..`"),
+            };
+            Ok(Segment::Descendant(selectors))
+        } else if self.eat(".") {
+            match self.peek() {
+                Some('*') => {
+                    self.i += 1;
+                    Ok(Segment::Child(vec![Selector::Wildcard]))
+                }
+                Some(c) if is_name_first(c) => {
+                    Ok(Segment::Child(vec![Selector::Name(self.member_name())]))
+                }
+                _ => self.error("expected a member name or `*` after `.`"),
+            }
+        } else {
+            Ok(Segment::Child(self.bracketed()?))
+        }
+    }
+
+    fn member_name(&mut self) -> String {
+        let start = self.i;
+        while let Some(c) = self.peek() {
+            if is_name_char(c) {
+                self.i += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+        self.src[start..self.i].to_string()
+    }
+
+    fn bracketed(&mut self) -> Result<Vec<Selector>, JsonPathError> {
+        self.expect("[")?;
+        let mut selectors = vec![];
+        loop {
+            self.whitespace();
+            selectors.push(self.selector()?);
+            self.whitespace();
+            if self.eat("]") {
+                return Ok(selectors);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, JsonPathError> {
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.i += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.i += 1;
+                self.whitespace();
+                Ok(Selector::Filter(Box::new(self.or()?)))
+            }
+            Some(':') => self.slice(None),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.integer()?;
+                self.whitespace();
+                if self.peek() == Some(':') {
+                    self.slice(Some(start))
+                } else {
+                    Ok(Selector::Index(start))
+                }
+            }
+            _ => self.error("expected a selector"),
+        }
+    }
+
+    fn slice(&mut self, start: Option<i64>) -> Result<Selector, JsonPathError> {
+        self.expect(":")?;
+        self.whitespace();
+        let end = self.optional_integer()?;
+        self.whitespace();
+        let step = if self.eat(":") {
+            self.whitespace();
+            self.optional_integer()?
+        } else {
+            None
+        };
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn optional_integer(&mut self) -> Result<Option<i64>, JsonPathError> {
+        match self.peek() {
+            Some(c) if c == '-' || c.is_ascii_digit() => self.integer().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, JsonPathError> {
+        let start = self.i;
+        self.eat("-");
+        let digits = self.i;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.i += 1;
+        }
+        let text = &self.src[start..self.i];
+        let digits = &self.src[digits..self.i];
+        if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0')) || text == "-0" {
+            self.i = start;
+            return self.error("invalid integer");
+        }
+        match text.parse::<i64>() {
+            Ok(n) if (-MAX_INDEX..=MAX_INDEX).contains(&n) => Ok(n),
+            _ => {
+                self.i = start;
+                self.error("integer out of range")
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonPathError> {
+        let quote = self.peek().unwrap();
+        self.i += 1;
+        let mut out = String::new();
+        loop {
+            let c = match self.peek() {
+                Some(c) => c,
+                None => return self.error("unterminated string"),
+            };
+            self.i += c.len_utf8();
+            match c {
+                c if c == quote => return Ok(out),
+                '\\' => out.push(self.string_escape(quote)?),
+                c if c < '\u{20}' => {
+                    self.i -= 1;
+                    return self.error("control character in string");
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonPathError> {
+        let digits = self.rest().get(..4).unwrap_or("");
+        if digits.len() == 4 && digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            self.i += 4;
+            Ok(u32::from_str_radix(digits, 16).unwrap())
+        } else {
+            self.error("invalid unicode escape")
+        }
+    }
+
+    fn string_escape(&mut self, quote: char) -> Result<char, JsonPathError> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return self.error("unterminated string"),
+        };
+        self.i += c.len_utf8();
+        match c {
+            'b' => Ok('\u{8}'),
+            'f' => Ok('\u{c}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            '/' => Ok('/'),
+            '\\' => Ok('\\'),
+            c if c == quote => Ok(c),
+            'u' => {
+                let unit = self.hex4()?;
+                let c = match unit {
+                    0xD800..=0xDBFF => {
+                        self.expect("\\u")?;
+                        let low = self.hex4()?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return self.error("invalid surrogate pair");
+                        }
+                        0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                    }
+                    0xDC00..=0xDFFF => return self.error("lone surrogate"),
+                    unit => unit,
+                };
+                Ok(::std::char::from_u32(c).unwrap())
+            }
+            _ => {
+                self.i -= c.len_utf8();
+                self.error("invalid escape")
+            }
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, JsonPathError> {
+        let mut operands = vec![self.and()?];
+        loop {
+            self.whitespace();
+            if !self.eat("||") {
+                break;
+            }
+            self.whitespace();
+            operands.push(self.and()?);
+        }
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            Expr::Or(operands)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, JsonPathError> {
+        let mut operands = vec![self.basic()?];
+        loop {
+            self.whitespace();
+            if !self.eat("&&") {
+                break;
+            }
+            self.whitespace();
+            operands.push(self.basic()?);
+        }
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            Expr::And(operands)
+        })
+    }
+
+    fn basic(&mut self) -> Result<Expr, JsonPathError> {
+        if self.eat("!") {
+            self.whitespace();
+            if self.eat("(") {
+                let expr = self.parenthesized()?;
+                return Ok(Expr::Not(Box::new(expr)));
+            }
+            let start = self.i;
+            return match self.comparable()? {
+                Comparable::Query(query) => Ok(Expr::Not(Box::new(Expr::Exists(query)))),
+                Comparable::Function(function) => self
+                    .test_function(function, start)
+                    .map(|expr| Expr::Not(Box::new(expr))),
+                Comparable::Literal(_) => {
+                    self.i = start;
+                    self.error("expected a query or function after `!`")
+                }
+            };
+        }
+        if self.eat("(") {
+            return self.parenthesized();
+        }
+
+        let start = self.i;
+        let left = self.comparable()?;
+        self.whitespace();
+        let op = match self.comparison() {
+            Some(op) => op,
+            None => {
+                return match left {
+                    Comparable::Query(query) => Ok(Expr::Exists(query)),
+                    Comparable::Function(function) => self.test_function(function, start),
+                    Comparable::Literal(_) => self.error("expected a comparison"),
+                }
+            }
+        };
+        self.whitespace();
+        let right_start = self.i;
+        let right = self.comparable()?;
+        self.check_comparable(&left, start)?;
+        self.check_comparable(&right, right_start)?;
+        Ok(Expr::Compare(left, op, right))
+    }
+
+    fn parenthesized(&mut self) -> Result<Expr, JsonPathError> {
+        self.whitespace();
+        let expr = self.or()?;
+        self.whitespace();
+        self.expect(")")?;
+        Ok(expr)
+    }
+
+    // Functions used as a test must produce a logical result
+    fn test_function(&self, function: Function, at: usize) -> Result<Expr, JsonPathError> {
+        match function.name {
+            FunctionName::Match | FunctionName::Search => Ok(Expr::Function(function)),
+            _ => Err(JsonPathError {
+                offset: at,
+                message: "function result must be compared".to_string(),
+            }),
+        }
+    }
+
+    // Only singular queries and value-producing functions can be compared
+    fn check_comparable(&self, comparable: &Comparable, at: usize) -> Result<(), JsonPathError> {
+        let message = match *comparable {
+            Comparable::Query(ref query) if !query.is_singular() => "query must be singular",
+            Comparable::Function(ref function)
+                if function.name == FunctionName::Match || function.name == FunctionName::Search =>
+            {
+                "function result cannot be compared"
+            }
+            _ => return Ok(()),
+        };
+        Err(JsonPathError {
+            offset: at,
+            message: message.to_string(),
+        })
+    }
+
+    fn comparison(&mut self) -> Option<Comparison> {
+        let ops = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ];
+        for &(text, op) in &ops {
+            if self.eat(text) {
+                return Some(op);
+            }
+        }
+        None
+    }
+
+    fn comparable(&mut self) -> Result<Comparable, JsonPathError> {
+        match self.peek() {
+            Some('$') | Some('@') => Ok(Comparable::Query(self.query()?)),
+            Some('\'') | Some('"') => Ok(Comparable::Literal(Literal::String(self.string()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                Ok(Comparable::Literal(Literal::Number(self.number()?)))
+            }
+            Some(c) if c.is_ascii_lowercase() => {
+                if self.eat("true") {
+                    Ok(Comparable::Literal(Literal::Bool(true)))
+                } else if self.eat("false") {
+                    Ok(Comparable::Literal(Literal::Bool(false)))
+                } else if self.eat("null") {
+                    Ok(Comparable::Literal(Literal::Null))
+                } else {
+                    Ok(Comparable::Function(self.function()?))
+                }
+            }
+            _ => self.error("expected a value"),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, JsonPathError> {
+        let start = self.i;
+        self.eat("-");
+        let int = self.i;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.i += 1;
+        }
+        let int = &self.src[int..self.i];
+        if int.is_empty() || (int.len() > 1 && int.starts_with('0')) {
+            self.i = start;
+            return self.error("invalid number");
+        }
+        if self.eat(".") {
+            let frac = self.i;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.i += 1;
+            }
+            if frac == self.i {
+                return self.error("expected digits after `.`");
+            }
+        }
+        if self.eat("e") || self.eat("E") {
+            if !self.eat("+") {
+                self.eat("-");
+            }
+            let exp = self.i;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.i += 1;
+            }
+            if exp == self.i {
+                return self.error("expected digits in exponent");
+            }
+        }
+        Ok(self.src[start..self.i].parse().unwrap())
+    }
+
+    fn function(&mut self) -> Result<Function, JsonPathError> {
+        let start = self.i;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            self.i += 1;
+        }
+        let name = match &self.src[start..self.i] {
+            "length" => FunctionName::Length,
+            "count" => FunctionName::Count,
+            "match" => FunctionName::Match,
+            "search" => FunctionName::Search,
+            "value" => FunctionName::Value,
+            _ => {
+                self.i = start;
+                return self.error("unknown function");
+            }
+        };
+        self.expect("(")?;
+
+        let mut args = vec![];
+        self.whitespace();
+        if !self.eat(")") {
+            loop {
+                let at = self.i;
+                let arg = self.comparable()?;
+                args.push((at, arg));
+                self.whitespace();
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+                self.whitespace();
+            }
+        }
+
+        let arity = match name {
+            FunctionName::Length | FunctionName::Count | FunctionName::Value => 1,
+            FunctionName::Match | FunctionName::Search => 2,
+        };
+        if args.len() != arity {
+            self.i = start;
+            return self.error("wrong number of function arguments");
+        }
+        for &(at, ref arg) in &args {
+            let takes_nodes = name == FunctionName::Count || name == FunctionName::Value;
+            match *arg {
+                // count() and value() take any query, the others a single
+                // value
+                Comparable::Query(_) if takes_nodes => {}
+                _ if takes_nodes => {
+                    return Err(JsonPathError {
+                        offset: at,
+                        message: "argument must be a query".to_string(),
+                    })
+                }
+                _ => self.check_comparable(arg, at)?,
+            }
+        }
+
+        Ok(Function {
+            name,
+            args: args.into_iter().map(|(_, arg)| arg).collect(),
+        })
+    }
+}
+
+fn parse(path: &str) -> Result<Query, JsonPathError> {
+    let mut parser = Parser { src: path, i: 0 };
+    let query = parser.query()?;
+    if query.relative {
+        return Err(JsonPathError {
+            offset: 0,
+            message: "expected `$`".to_string(),
+        });
+    }
+    if parser.i != path.len() {
+        return parser.error("unexpected character");
+    }
+    Ok(query)
+}
+
+// Evaluation
+
+type Nodes<'a> = Vec<(&'a Node, Vec<PathSegment>)>;
+
+// The result of evaluating one side of a comparison or a function
+enum Value<'a> {
+    // An empty query result, or a function applied to one
+    Nothing,
+    Node(&'a Node),
+    Number(f64),
+    String(Cow<'a, str>),
+    Bool(bool),
+    Null,
+}
+
+impl<'a> Value<'a> {
+    // Scalars are compared by value no matter where they came from
+    fn from_node(node: &'a Node) -> Value<'a> {
+        match *node {
+            Node::Number { ref raw, .. } => Value::Number(raw.parse().unwrap_or(0.0)),
+            Node::String { .. } => Value::String(node.decoded().unwrap()),
+            Node::Boolean { .. } => Value::Bool(node.as_bool().unwrap()),
+            Node::Null { .. } => Value::Null,
+            Node::Object { .. } | Node::Array { .. } => Value::Node(node),
+        }
+    }
+
+    fn from_literal(literal: &'a Literal) -> Value<'a> {
+        match *literal {
+            Literal::Number(n) => Value::Number(n),
+            Literal::String(ref s) => Value::String(Cow::Borrowed(s)),
+            Literal::Bool(b) => Value::Bool(b),
+            Literal::Null => Value::Null,
+        }
+    }
+}
+
+pub fn nodes_equal(a: &Node, b: &Node) -> bool {
+    match (a, b) {
+        (Node::Object { children: a, .. }, Node::Object { children: b, .. }) => {
+            a.len() == b.len()
+                && a.iter().all(|property| {
+                    b.iter().any(|other| {
+                        other.key.decoded() == property.key.decoded()
+                            && nodes_equal(&property.value, &other.value)
+                    })
+                })
+        }
+        (Node::Array { children: a, .. }, Node::Array { children: b, .. }) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| nodes_equal(a, b))
+        }
+        (Node::Object { .. }, _) | (Node::Array { .. }, _) => false,
+        (_, Node::Object { .. }) | (_, Node::Array { .. }) => false,
+        _ => values_equal(&Value::from_node(a), &Value::from_node(b)),
+    }
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (&Value::Nothing, &Value::Nothing) => true,
+        (&Value::Node(a), &Value::Node(b)) => nodes_equal(a, b),
+        (&Value::Number(a), &Value::Number(b)) => a == b,
+        (Value::String(a), Value::String(b)) => a == b,
+        (&Value::Bool(a), &Value::Bool(b)) => a == b,
+        (&Value::Null, &Value::Null) => true,
+        _ => false,
+    }
+}
+
+fn value_less(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (&Value::Number(a), &Value::Number(b)) => a < b,
+        (Value::String(a), Value::String(b)) => a < b,
+        _ => false,
+    }
+}
+
+struct Evaluator<'a> {
+    root: &'a Node,
+}
+
+fn descendants<'a>(node: &'a Node, path: Vec<PathSegment>, out: &mut Nodes<'a>) {
+    out.push((node, path.clone()));
+    match *node {
+        Node::Object { ref children, .. } => {
+            for property in children {
+                let mut child_path = path.clone();
+                child_path.push(PathSegment::Key(property.key.decoded().into_owned()));
+                descendants(&property.value, child_path, out);
+            }
+        }
+        Node::Array { ref children, .. } => {
+            for (i, child) in children.iter().enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(PathSegment::Index(i));
+                descendants(child, child_path, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+// The indexes selected by `start:end:step` over an array of `len` elements
+// (RFC 9535, section 2.3.4.2.2)
+fn slice_indexes(len: usize, start: Option<i64>, end: Option<i64>, step: Option<i64>) -> Vec<usize> {
+    let len = len as i64;
+    let step = step.unwrap_or(1);
+    if step == 0 {
+        return vec![];
+    }
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+
+    let mut indexes = vec![];
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).max(0).min(len);
+        let upper = normalize(end.unwrap_or(len)).max(0).min(len);
+        let mut i = lower;
+        while i < upper {
+            indexes.push(i as usize);
+            i += step;
+        }
+    } else {
+        let upper = normalize(start.unwrap_or(len - 1)).max(-1).min(len - 1);
+        let lower = normalize(end.unwrap_or(-len - 1)).max(-1).min(len - 1);
+        let mut i = upper;
+        while lower < i {
+            indexes.push(i as usize);
+            i += step;
+        }
+    }
+    indexes
+}
+
+impl<'a> Evaluator<'a> {
+    fn query(&self, query: &Query, current: &'a Node) -> Nodes<'a> {
+        let start = if query.relative { current } else { self.root };
+        let mut nodes = vec![(start, vec![])];
+        for segment in &query.segments {
+            let mut next = vec![];
+            for (node, path) in nodes {
+                match *segment {
+                    Segment::Child(ref selectors) => {
+                        for selector in selectors {
+                            self.select(selector, node, &path, &mut next);
+                        }
+                    }
+                    Segment::Descendant(ref selectors) => {
+                        let mut all = vec![];
+                        descendants(node, path, &mut all);
+                        for (descendant, descendant_path) in all {
+                            for selector in selectors {
+                                self.select(selector, descendant, &descendant_path, &mut next);
+                            }
+                        }
+                    }
+                }
+            }
+            nodes = next;
+        }
+        nodes
+    }
+
+    fn select(&self, selector: &Selector, node: &'a Node, path: &[PathSegment], out: &mut Nodes<'a>) {
+        let push = |out: &mut Nodes<'a>, child: &'a Node, segment: PathSegment| {
+            let mut child_path = path.to_vec();
+            child_path.push(segment);
+            out.push((child, child_path));
+        };
+
+        match *selector {
+            Selector::Name(ref name) => {
+                if let Node::Object { ref children, .. } = *node {
+                    for property in children.iter().filter(|property| property.key.matches(name)) {
+                        push(out, &property.value, PathSegment::Key(name.clone()));
+                    }
+                }
+            }
+            Selector::Wildcard | Selector::Filter(_) => {
+                let children: Vec<(&'a Node, PathSegment)> = match *node {
+                    Node::Object { ref children, .. } => children
+                        .iter()
+                        .map(|property| {
+                            let key = property.key.decoded().into_owned();
+                            (&property.value, PathSegment::Key(key))
+                        })
+                        .collect(),
+                    Node::Array { ref children, .. } => children
+                        .iter()
+                        .enumerate()
+                        .map(|(i, child)| (child, PathSegment::Index(i)))
+                        .collect(),
+                    _ => vec![],
+                };
+                for (child, segment) in children {
+                    let selected = match *selector {
+                        Selector::Filter(ref expr) => self.test(expr, child),
+                        _ => true,
+                    };
+                    if selected {
+                        push(out, child, segment);
+                    }
+                }
+            }
+            Selector::Index(index) => {
+                if let Node::Array { ref children, .. } = *node {
+                    let i = if index < 0 {
+                        children.len() as i64 + index
+                    } else {
+                        index
+                    };
+                    if 0 <= i && i < children.len() as i64 {
+                        push(out, &children[i as usize], PathSegment::Index(i as usize));
+                    }
+                }
+            }
+            Selector::Slice(start, end, step) => {
+                if let Node::Array { ref children, .. } = *node {
+                    for i in slice_indexes(children.len(), start, end, step) {
+                        push(out, &children[i], PathSegment::Index(i));
+                    }
+                }
+            }
+        }
+    }
+
+    fn test(&self, expr: &Expr, current: &'a Node) -> bool {
+        match *expr {
+            Expr::Or(ref operands) => operands.iter().any(|expr| self.test(expr, current)),
+            Expr::And(ref operands) => operands.iter().all(|expr| self.test(expr, current)),
+            Expr::Not(ref expr) => !self.test(expr, current),
+            Expr::Exists(ref query) => !self.query(query, current).is_empty(),
+            Expr::Function(ref function) => match self.call(function, current) {
+                Value::Bool(b) => b,
+                _ => false,
+            },
+            Expr::Compare(ref left, op, ref right) => {
+                let left = self.value(left, current);
+                let right = self.value(right, current);
+                match op {
+                    Comparison::Equal => values_equal(&left, &right),
+                    Comparison::NotEqual => !values_equal(&left, &right),
+                    Comparison::Less => value_less(&left, &right),
+                    Comparison::LessOrEqual => {
+                        value_less(&left, &right) || values_equal(&left, &right)
+                    }
+                    Comparison::Greater => value_less(&right, &left),
+                    Comparison::GreaterOrEqual => {
+                        value_less(&right, &left) || values_equal(&left, &right)
+                    }
+                }
+            }
+        }
+    }
+
+    fn value(&self, comparable: &'a Comparable, current: &'a Node) -> Value<'a> {
+        match *comparable {
+            Comparable::Literal(ref literal) => Value::from_literal(literal),
+            Comparable::Query(ref query) => match self.query(query, current).first() {
+                Some(&(node, _)) => Value::from_node(node),
+                None => Value::Nothing,
+            },
+            Comparable::Function(ref function) => self.call(function, current),
+        }
+    }
+
+    fn call(&self, function: &'a Function, current: &'a Node) -> Value<'a> {
+        let nodes = |arg: &Comparable| match *arg {
+            Comparable::Query(ref query) => self.query(query, current),
+            _ => vec![],
+        };
+
+        match function.name {
+            FunctionName::Length => match self.value(&function.args[0], current) {
+                Value::String(ref s) => Value::Number(s.chars().count() as f64),
+                Value::Node(Node::Object { children, .. }) => {
+                    Value::Number(children.len() as f64)
+                }
+                Value::Node(Node::Array { children, .. }) => {
+                    Value::Number(children.len() as f64)
+                }
+                _ => Value::Nothing,
+            },
+            FunctionName::Count => Value::Number(nodes(&function.args[0]).len() as f64),
+            FunctionName::Value => {
+                let nodes = nodes(&function.args[0]);
+                if nodes.len() == 1 {
+                    Value::from_node(nodes[0].0)
+                } else {
+                    Value::Nothing
+                }
+            }
+            FunctionName::Match | FunctionName::Search => {
+                let input = self.value(&function.args[0], current);
+                let pattern = self.value(&function.args[1], current);
+                let result = match (input, pattern) {
+                    (Value::String(ref input), Value::String(ref pattern)) => {
+                        match Regex::new(pattern) {
+                            Some(ref re) if function.name == FunctionName::Match => {
+                                re.is_match(input)
+                            }
+                            Some(ref re) => re.is_found(input),
+                            None => false,
+                        }
+                    }
+                    _ => false,
+                };
+                Value::Bool(result)
+            }
+        }
+    }
+}
+
+// Evaluates a JSONPath query (RFC 9535) such as `$..servers[?@.port > 1024]`
+// against `root`, returning the selected nodes in document order
+pub fn query<'a>(root: &'a Node, path: &str) -> Result<Vec<QueryMatch<'a>>, JsonPathError> {
+    let query = parse(path)?;
+    let evaluator = Evaluator { root };
+    Ok(evaluator
+        .query(&query, root)
+        .into_iter()
+        .map(|(node, path)| QueryMatch { node, path })
+        .collect())
+}
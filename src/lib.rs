@@ -2,22 +2,128 @@
 mod tokenize;
 mod parse;
 mod types;
+mod strings;
+mod number;
+mod precision;
+mod visit;
+mod pointer;
+mod location;
+mod iregexp;
+mod jsonpath;
+mod tree;
+mod lines;
+mod document;
+mod format;
+pub mod edit;
+mod minify;
+mod canonical;
+mod patch;
+mod merge;
+mod merge3;
+mod diff;
+mod sort;
 
 use tokenize::{tokenize};
 use parse::parse_value;
-pub use types::{Node, Parsed};
+use types::{Token, TokenType};
+pub use canonical::{canonicalize, CanonicalError};
+pub use diff::{diff, diff_with, Change, ChangeKind, DiffOptions};
+pub use document::{Document, Member, NodeKind};
+pub use edit::{apply_edits, EditError, TextEdit};
+pub use format::{format, format_range, format_with_comments, FormatOptions, Indent, KeySpacing,
+                 LineEnding};
+pub use jsonpath::{query, JsonPathError, QueryMatch};
+pub use lines::{CompactSpan, LineTable, OffsetTooLarge};
+pub use location::{Location, LocationKind};
+pub use merge::{merge_patch, merge_patches, Merged, Origin};
+pub use merge3::{merge3, Conflict, Merge3, Merge3Error};
+pub use minify::{minify, minify_str, minify_to};
+pub use number::{Decimal, NumberError};
+pub use patch::{apply_patch, PatchError};
+pub use pointer::{escape as escape_pointer, to_pointer, PathSegment};
+pub use precision::{check_precision, PrecisionIssue, PrecisionWarning};
+pub use sort::{check_key_order, sort_keys, KeyOrder, SortOptions};
+pub use strings::SourceMap;
+pub use tree::{Ancestors, NodeId, TreeIndex};
+pub use visit::{walk_array, walk_array_mut, walk_node, walk_node_mut, walk_object, walk_object_mut,
+                walk_property, walk_property_mut, Visitor, VisitorMut};
+pub use types::{Comment, CommentKind, Identifier, Node, ParseError, ParseErrorType, Parsed, Position,
+                Property, Span, StringError, StringStatus};
 
 
 pub fn parse(input: &str) -> Parsed {
-    let tokens = tokenize(input);
+    let (tokens, comments, error) = tokenize_jsonc(input);
+    let parsed = parse_tokens(&tokens, error);
+    if comments.is_empty() {
+        return parsed;
+    }
 
-    if tokens.len() == 0 {
+    let comment_errors = comments.iter().map(|comment| ParseError {
+        position: comment.span.start,
+        error_type: ParseErrorType::Comment,
+    });
+    match parsed {
+        Parsed::Success { tree } => Parsed::Failure {
+            tokens,
+            tree: Some(tree),
+            errors: comment_errors.collect(),
+        },
         Parsed::Failure {
             tokens,
+            tree,
+            mut errors,
+        } => {
+            errors.extend(comment_errors);
+            Parsed::Failure {
+                tokens,
+                tree,
+                errors,
+            }
+        }
+    }
+}
+
+// Tokenizes `input`, moving the comments out of the tokens
+fn tokenize_jsonc(input: &str) -> (Vec<Token>, Vec<Comment>, Option<ParseError>) {
+    let (mut tokens, error) = tokenize(input);
+    let mut comments = vec![];
+    tokens.retain(|token| {
+        let kind = match token.kind {
+            TokenType::LineComment => CommentKind::Line,
+            TokenType::BlockComment => CommentKind::Block,
+            _ => return true,
+        };
+        comments.push(Comment {
+            kind,
+            text: token.value.clone().unwrap_or_default(),
+            span: token.span,
+        });
+        false
+    });
+    (tokens, comments, error)
+}
+
+fn parse_tokens(tokens: &[Token], error: Option<ParseError>) -> Parsed {
+    if let Some(error) = error {
+        Parsed::Failure {
+            tokens: tokens.to_vec(),
+            tree: None,
+            errors: vec!(error),
+        }
+    } else if tokens.is_empty() {
+        Parsed::Failure {
+            tokens: vec!(),
             tree: None,
             errors: vec!(),
         }
     } else {
-        parse_value(&tokens)
+        parse_value(tokens)
     }
 }
+
+// Parses JSON with comments (JSONC, as used by tsconfig.json and VS Code
+// settings), returning the comments separately from the tree
+pub fn parse_jsonc(input: &str) -> (Parsed, Vec<Comment>) {
+    let (tokens, comments, error) = tokenize_jsonc(input);
+    (parse_tokens(&tokens, error), comments)
+}
@@ -0,0 +1,122 @@
+use std::convert::TryFrom;
+
+use types::{Position, Span};
+
+// A span as a pair of byte offsets, a quarter of the size of a `Span`. The
+// line and column of each end can be recovered from the LineTable of the
+// document it came from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CompactSpan {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl CompactSpan {
+    pub fn len(&self) -> usize {
+        (self.end - self.start) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    // Whether the byte at `offset` is inside the span
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start as usize <= offset && offset < self.end as usize
+    }
+
+    pub fn text<'a>(&self, src: &'a str) -> &'a str {
+        &src[self.start as usize..self.end as usize]
+    }
+}
+
+// A span ending past 4GiB, which doesn't fit in a CompactSpan
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OffsetTooLarge(pub Span);
+
+impl TryFrom<Span> for CompactSpan {
+    type Error = OffsetTooLarge;
+
+    fn try_from(span: Span) -> Result<CompactSpan, OffsetTooLarge> {
+        match (u32::try_from(span.start.index), u32::try_from(span.end.index)) {
+            (Ok(start), Ok(end)) => Ok(CompactSpan { start, end }),
+            _ => Err(OffsetTooLarge(span)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Line {
+    // The first recorded offset on the line
+    start: u32,
+    line: u32,
+    // The offset of column 1
+    origin: u32,
+}
+
+// Maps byte offsets back to full positions. The table is built from the
+// positions the tokenizer recorded rather than by rescanning the source, so
+// recovered positions are exactly the ones in the tree, however the
+// tokenizer counted lines and columns.
+#[derive(Clone, Debug, Default)]
+pub struct LineTable {
+    lines: Vec<Line>,
+}
+
+impl LineTable {
+    pub fn new<I: IntoIterator<Item = Position>>(positions: I) -> LineTable {
+        let mut lines: Vec<Line> = positions
+            .into_iter()
+            .map(|position| Line {
+                start: position.index as u32,
+                line: position.line as u32,
+                origin: (position.index + 1 - position.column) as u32,
+            })
+            .collect();
+        lines.sort_by_key(|line| (line.start, line.line));
+        lines.dedup_by_key(|line| line.line);
+        lines.shrink_to_fit();
+        LineTable { lines }
+    }
+
+    // Records a position at or after every one recorded before, for
+    // building a table while reading a document from the start
+    pub fn push(&mut self, position: Position) {
+        if self.lines.last().is_some_and(|last| last.line as usize == position.line) {
+            return;
+        }
+        self.lines.push(Line {
+            start: position.index as u32,
+            line: position.line as u32,
+            origin: (position.index + 1 - position.column) as u32,
+        });
+    }
+
+    // The position of `offset`, which should be one of the recorded ones.
+    // Other offsets get the line of the closest recorded position before
+    // them.
+    pub fn position(&self, offset: u32) -> Position {
+        let i = self.lines.partition_point(|line| line.start <= offset);
+        let line = if i == 0 {
+            Line {
+                start: 0,
+                line: 1,
+                origin: 0,
+            }
+        } else {
+            self.lines[i - 1]
+        };
+        Position {
+            line: line.line as usize,
+            column: (offset.saturating_sub(line.origin) + 1) as usize,
+            index: offset as usize,
+        }
+    }
+
+    pub fn span(&self, span: CompactSpan) -> Span {
+        Span {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+}
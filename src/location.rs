@@ -0,0 +1,107 @@
+use pointer::PathSegment;
+use types::{Node, Property};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LocationKind {
+    // On an Object member's key
+    Key,
+    // On a value, including the braces and brackets of a container
+    Value,
+    // On a `:` or `,`
+    Separator,
+    // Anywhere else inside a container
+    Whitespace,
+}
+
+#[derive(Clone, Debug)]
+pub struct Location<'a> {
+    pub kind: LocationKind,
+    // The value under the cursor. For a key, that's the member's value; for
+    // separators and whitespace it's the enclosing container.
+    pub node: &'a Node,
+    // The member whose key, value or `:` the cursor is on
+    pub property: Option<&'a Property>,
+    // Every node from the root down to the parent of `node`
+    pub ancestors: Vec<&'a Node>,
+    // The keys and indexes leading from the root to `node`
+    pub path: Vec<PathSegment>,
+}
+
+fn separator(src: &str, offset: usize) -> LocationKind {
+    match src.as_bytes().get(offset) {
+        Some(&b':') | Some(&b',') => LocationKind::Separator,
+        _ => LocationKind::Whitespace,
+    }
+}
+
+// Finds what is at byte `offset` of `src`, the document `root` was parsed
+// from
+pub fn node_at<'a>(root: &'a Node, src: &str, offset: usize) -> Option<Location<'a>> {
+    if !root.span().contains(offset) {
+        return None;
+    }
+
+    let mut location = Location {
+        kind: LocationKind::Value,
+        node: root,
+        property: None,
+        ancestors: vec![],
+        path: vec![],
+    };
+
+    loop {
+        let node = location.node;
+        let (child, segment, property) = match *node {
+            Node::Object { ref children, .. } => {
+                let found = children
+                    .iter()
+                    .find(|property| property.span.contains(offset));
+                match found {
+                    Some(property) if property.value.span().contains(offset) => {
+                        let key = property.key.decoded().into_owned();
+                        (&property.value, PathSegment::Key(key), Some(property))
+                    }
+                    Some(property) => {
+                        location.property = Some(property);
+                        if property.key.span.contains(offset) {
+                            let key = property.key.decoded().into_owned();
+                            location.kind = LocationKind::Key;
+                            location.ancestors.push(node);
+                            location.path.push(PathSegment::Key(key));
+                            location.node = &property.value;
+                        } else {
+                            location.kind = separator(src, offset);
+                        }
+                        return Some(location);
+                    }
+                    None => break,
+                }
+            }
+            Node::Array { ref children, .. } => {
+                let found = children
+                    .iter()
+                    .enumerate()
+                    .find(|&(_, child)| child.span().contains(offset));
+                match found {
+                    Some((i, child)) => (child, PathSegment::Index(i), None),
+                    None => break,
+                }
+            }
+            _ => return Some(location),
+        };
+
+        location.ancestors.push(node);
+        location.path.push(segment);
+        location.property = property;
+        location.node = child;
+    }
+
+    // Inside a container but not on any of its members, either on its own
+    // braces or in between members
+    let span = location.node.span();
+    if offset != span.start.index && offset + 1 != span.end.index {
+        location.kind = separator(src, offset);
+        location.property = None;
+    }
+    Some(location)
+}
@@ -0,0 +1,259 @@
+use pointer;
+use tree::NodeId;
+use types::{Identifier, Node, Property, PropertyStatus, Span};
+
+// Where a node of a merged tree came from: the document it was taken from,
+// numbered from 0 for the base, and its span there
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Origin {
+    pub source: usize,
+    pub span: Span,
+}
+
+// The result of a merge patch. Nodes of `tree` keep the spans they had in
+// their own documents, and `origins` has the Origin of every node, indexed
+// by NodeId.
+#[derive(Clone, Debug)]
+pub struct Merged {
+    pub tree: Node,
+    pub origins: Vec<Origin>,
+    // How many nodes each subtree has, indexed by NodeId
+    sizes: Vec<usize>,
+}
+
+impl Merged {
+    pub fn origin(&self, id: NodeId) -> Origin {
+        self.origins[id.0]
+    }
+
+    // The Origin of the node at a JSON Pointer into the merged tree. Ids
+    // are worked out on the way down from the subtree sizes, as a child
+    // comes right after its parent and the subtrees of its earlier
+    // siblings.
+    pub fn origin_at(&self, pointer: &str) -> Option<Origin> {
+        let mut id = 0;
+        let mut node = &self.tree;
+        for token in pointer::tokens(pointer)? {
+            let (index, child) = match *node {
+                Node::Object { ref children, .. } => {
+                    let index = children.iter().rposition(|property| property.key.matches(&token))?;
+                    (index, &children[index].value)
+                }
+                Node::Array { ref children, .. } => {
+                    let index = pointer::array_index(&token)?;
+                    (index, children.get(index)?)
+                }
+                _ => return None,
+            };
+            id += 1;
+            for _ in 0..index {
+                id += self.sizes[id];
+            }
+            node = child;
+        }
+        Some(self.origins[id])
+    }
+}
+
+struct Member {
+    status: PropertyStatus,
+    key: Identifier,
+    span: Span,
+    value: Merging,
+}
+
+// A tree part way through being merged. Objects that patches have merged
+// into are taken apart, anything else is kept whole with the document it
+// came from.
+enum Merging {
+    Object {
+        source: usize,
+        span: Span,
+        members: Vec<Member>,
+    },
+    Value {
+        source: usize,
+        node: Node,
+    },
+}
+
+impl Merging {
+    fn is_object(&self) -> bool {
+        match *self {
+            Merging::Object { .. } => true,
+            Merging::Value { ref node, .. } => node.as_object().is_some(),
+        }
+    }
+}
+
+fn open(target: Option<Merging>, patch: &Node, source: usize) -> (usize, Span, Vec<Member>) {
+    match target {
+        Some(Merging::Object {
+            source,
+            span,
+            members,
+        }) => (source, span, members),
+        Some(Merging::Value {
+            source,
+            node: Node::Object { children, span },
+        }) => {
+            let members = children
+                .into_iter()
+                .map(|property| Member {
+                    status: property.status,
+                    key: property.key,
+                    span: property.span,
+                    value: Merging::Value {
+                        source,
+                        node: property.value,
+                    },
+                })
+                .collect();
+            (source, span, members)
+        }
+        // Anything that isn't an Object is replaced by a new one
+        _ => (source, patch.span(), vec![]),
+    }
+}
+
+// The MergePatch function of RFC 7396
+fn merge(target: Option<Merging>, patch: &Node, source: usize) -> Merging {
+    let children = match patch.as_object() {
+        Some(children) => children,
+        None => {
+            return Merging::Value {
+                source,
+                node: patch.clone(),
+            }
+        }
+    };
+
+    let (target_source, span, mut members) = open(target, patch, source);
+    for property in children {
+        let key = property.key.decoded();
+        if property.value.is_null() {
+            members.retain(|member| member.key.decoded() != key);
+            continue;
+        }
+        match members.iter().rposition(|member| member.key.decoded() == key) {
+            // A member merged into an Object keeps its key from the
+            // document that Object came from, one replaced takes the
+            // patch's
+            Some(i) if members[i].value.is_object() && property.value.as_object().is_some() => {
+                let member = members.remove(i);
+                let value = merge(Some(member.value), &property.value, source);
+                members.insert(i, Member { value, ..member });
+            }
+            Some(i) => {
+                members[i] = Member {
+                    status: property.status.clone(),
+                    key: property.key.clone(),
+                    span: property.span,
+                    value: merge(None, &property.value, source),
+                }
+            }
+            None => members.push(Member {
+                status: property.status.clone(),
+                key: property.key.clone(),
+                span: property.span,
+                value: merge(None, &property.value, source),
+            }),
+        }
+    }
+    Merging::Object {
+        source: target_source,
+        span,
+        members,
+    }
+}
+
+fn count(node: &Node, sizes: &mut Vec<usize>) -> usize {
+    let id = sizes.len();
+    sizes.push(1);
+    let size = 1 + match *node {
+        Node::Object { ref children, .. } => children
+            .iter()
+            .map(|property| count(&property.value, sizes))
+            .sum(),
+        Node::Array { ref children, .. } => children.iter().map(|child| count(child, sizes)).sum(),
+        _ => 0,
+    };
+    sizes[id] = size;
+    size
+}
+
+fn record(node: &Node, source: usize, origins: &mut Vec<Origin>) {
+    origins.push(Origin {
+        source,
+        span: node.span(),
+    });
+    match *node {
+        Node::Object { ref children, .. } => {
+            for property in children {
+                record(&property.value, source, origins);
+            }
+        }
+        Node::Array { ref children, .. } => {
+            for child in children {
+                record(child, source, origins);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Puts a merged tree back together, numbering origins in document order
+fn finish(merging: Merging, origins: &mut Vec<Origin>) -> Node {
+    match merging {
+        Merging::Value { source, node } => {
+            record(&node, source, origins);
+            node
+        }
+        Merging::Object {
+            source,
+            span,
+            members,
+        } => {
+            origins.push(Origin { source, span });
+            let children = members
+                .into_iter()
+                .map(|member| Property {
+                    status: member.status,
+                    key: member.key,
+                    value: finish(member.value, origins),
+                    span: member.span,
+                })
+                .collect();
+            Node::Object { children, span }
+        }
+    }
+}
+
+// Applies JSON Merge Patches (RFC 7396) to `base` in order, as when layering
+// configuration files. Source 0 is `base` and source `i + 1` is
+// `patches[i]`. An Object that patches merge into keeps its Origin in the
+// document that first defined it; every other node comes from the document
+// whose value was used. Members with duplicate keys count as the last one,
+// and a null removes all of them.
+pub fn merge_patches(base: &Node, patches: &[&Node]) -> Merged {
+    let mut merging = Merging::Value {
+        source: 0,
+        node: base.clone(),
+    };
+    for (i, patch) in patches.iter().enumerate() {
+        merging = merge(Some(merging), patch, i + 1);
+    }
+    let mut origins = vec![];
+    let tree = finish(merging, &mut origins);
+    let mut sizes = Vec::with_capacity(origins.len());
+    count(&tree, &mut sizes);
+    Merged {
+        tree,
+        origins,
+        sizes,
+    }
+}
+
+pub fn merge_patch(base: &Node, patch: &Node) -> Merged {
+    merge_patches(base, &[patch])
+}
@@ -0,0 +1,135 @@
+use diff::{diff_with, DiffOptions};
+use edit::{apply_edits, matches_source, remove, set, EditError};
+use patch::reparse;
+use pointer::escape;
+use types::{Node, Span};
+
+// A value both sides changed in different ways. The merge keeps ours.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub pointer: String,
+    // The spans of the value in each input, None where it is missing
+    pub base: Option<Span>,
+    pub ours: Option<Span>,
+    pub theirs: Option<Span>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Merge3 {
+    pub text: String,
+    pub tree: Node,
+    pub conflicts: Vec<Conflict>,
+}
+
+// Why theirs' changes couldn't be made to `ours_src`, with the pointer of
+// the change where there is one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Merge3Error {
+    // `ours_src` isn't the text `ours` was parsed from
+    SourceMismatch,
+    Edit(String, EditError),
+    // The edits left text that no longer parses, which can happen when
+    // `ours_src` differs from the text of `ours` only between tokens
+    Unparsable(String),
+}
+
+// A change from theirs to make to ours: a value to set, or None to remove
+type Take = (String, Option<Node>);
+
+fn same(a: Option<&Node>, b: Option<&Node>) -> bool {
+    let options = DiffOptions {
+        ignore_key_order: true,
+        ..DiffOptions::default()
+    };
+    match (a, b) {
+        (Some(a), Some(b)) => diff_with(a, b, &options).is_empty(),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+// Keys in the order they first appear in ours, then theirs, then base
+fn keys(nodes: &[Option<&Node>]) -> Vec<String> {
+    let mut keys: Vec<String> = vec![];
+    for node in nodes.iter().flatten() {
+        for key in node.keys() {
+            let key = key.decoded();
+            if !keys.iter().any(|other| *other == key) {
+                keys.push(key.into_owned());
+            }
+        }
+    }
+    keys
+}
+
+fn member<'a>(node: Option<&'a Node>, key: &str) -> Option<&'a Node> {
+    node.and_then(|node| node.get(key))
+}
+
+fn merge(
+    base: Option<&Node>,
+    ours: Option<&Node>,
+    theirs: Option<&Node>,
+    pointer: String,
+    takes: &mut Vec<Take>,
+    conflicts: &mut Vec<Conflict>,
+) {
+    let is_object = |node: Option<&Node>| node.is_some_and(|node| node.as_object().is_some());
+    // Objects are merged member by member. One added on both sides is
+    // merged as if it had been empty.
+    if is_object(ours) && is_object(theirs) && (base.is_none() || is_object(base)) {
+        for key in keys(&[ours, theirs, base]) {
+            let pointer = format!("{}/{}", pointer, escape(&key));
+            let (base, ours, theirs) = (member(base, &key), member(ours, &key), member(theirs, &key));
+            merge(base, ours, theirs, pointer, takes, conflicts);
+        }
+    } else if same(ours, theirs) || same(base, theirs) {
+        // Nothing to take from theirs
+    } else if same(base, ours) {
+        takes.push((pointer, theirs.cloned()));
+    } else {
+        conflicts.push(Conflict {
+            pointer,
+            base: base.map(Node::span),
+            ours: ours.map(Node::span),
+            theirs: theirs.map(Node::span),
+        });
+    }
+}
+
+// Merges the changes from `base` to `theirs` into `ours`, which was parsed
+// from `ours_src`. Objects are merged member by member, anything else is
+// taken whole from whichever side changed it. Values both sides changed
+// differently are conflicts, and keep ours.
+//
+// The text is `ours_src` with theirs' changes made as edits, so it keeps
+// the formatting and comments of ours everywhere theirs didn't change.
+// Making those edits fails if `ours_src` isn't what `ours` was parsed from.
+pub fn merge3(base: &Node, ours_src: &str, ours: &Node, theirs: &Node) -> Result<Merge3, Merge3Error> {
+    if !matches_source(ours_src, ours) {
+        return Err(Merge3Error::SourceMismatch);
+    }
+    let mut takes = vec![];
+    let mut conflicts = vec![];
+    merge(Some(base), Some(ours), Some(theirs), String::new(), &mut takes, &mut conflicts);
+
+    let mut text = ours_src.to_string();
+    let mut tree = ours.clone();
+    for (pointer, value) in takes {
+        let edits = match value {
+            Some(ref value) => set(&text, &tree, &pointer, value),
+            None => remove(&text, &tree, &pointer),
+        };
+        let error = |error| Merge3Error::Edit(pointer.clone(), error);
+        let edits = edits.map_err(error)?;
+        if !edits.is_empty() {
+            text = apply_edits(&text, &edits).map_err(error)?;
+            tree = reparse(&text).ok_or_else(|| Merge3Error::Unparsable(pointer.clone()))?;
+        }
+    }
+    Ok(Merge3 {
+        text,
+        tree,
+        conflicts,
+    })
+}
@@ -0,0 +1,100 @@
+use std::io::{self, Write};
+
+use tokenize::Tokens;
+use types::{Node, ParseError, Token, TokenType};
+
+fn minify_node(node: &Node, out: &mut String) {
+    match *node {
+        Node::Object { ref children, .. } => {
+            out.push('{');
+            for (i, property) in children.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push('"');
+                out.push_str(&property.key.raw);
+                out.push_str("\":");
+                minify_node(&property.value, out);
+            }
+            out.push('}');
+        }
+        Node::Array { ref children, .. } => {
+            out.push('[');
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                minify_node(child, out);
+            }
+            out.push(']');
+        }
+        Node::String { ref raw, .. } => {
+            out.push('"');
+            out.push_str(raw);
+            out.push('"');
+        }
+        Node::Number { ref raw, .. } | Node::Boolean { ref raw, .. } | Node::Null { ref raw, .. } => {
+            out.push_str(raw)
+        }
+    }
+}
+
+// Prints a tree without any whitespace. Number and string lexemes are kept
+// exactly as written.
+pub fn minify(node: &Node) -> String {
+    let mut out = String::new();
+    minify_node(node, &mut out);
+    out
+}
+
+fn write_token<W: Write>(token: &Token, keep_comments: bool, out: &mut W) -> io::Result<()> {
+    let text = match token.kind {
+        TokenType::LeftBrace => "{",
+        TokenType::RightBrace => "}",
+        TokenType::LeftBracket => "[",
+        TokenType::RightBracket => "]",
+        TokenType::Colon => ":",
+        TokenType::Comma => ",",
+        TokenType::String => {
+            return write!(out, "\"{}\"", token.value.as_ref().unwrap());
+        }
+        TokenType::LineComment | TokenType::BlockComment if !keep_comments => return Ok(()),
+        // A line comment runs to the end of the line, so needs one
+        TokenType::LineComment => {
+            return writeln!(out, "{}", token.value.as_ref().unwrap());
+        }
+        TokenType::Number
+        | TokenType::True
+        | TokenType::False
+        | TokenType::Null
+        | TokenType::BlockComment => token.value.as_ref().unwrap(),
+    };
+    out.write_all(text.as_bytes())
+}
+
+// Minifies source text token by token, without building a tree, writing
+// the result to `out` as it goes. Comments are dropped unless
+// `keep_comments` is set. Text that isn't a token stops the output with an
+// InvalidData error.
+pub fn minify_to<W: Write>(input: &str, keep_comments: bool, out: &mut W) -> io::Result<()> {
+    for token in Tokens::new(input) {
+        let token = token.map_err(|error| {
+            let message = format!(
+                "invalid token at line {}, column {}",
+                error.position.line, error.position.column
+            );
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        })?;
+        write_token(&token, keep_comments, out)?;
+    }
+    Ok(())
+}
+
+pub fn minify_str(input: &str, keep_comments: bool) -> Result<String, ParseError> {
+    let mut out = vec![];
+    for token in Tokens::new(input) {
+        // Writing to a Vec can't fail
+        write_token(&token?, keep_comments, &mut out).unwrap();
+    }
+    Ok(String::from_utf8(out).unwrap())
+}
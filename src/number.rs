@@ -0,0 +1,174 @@
+use std::convert::TryFrom;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NumberError {
+    // The node is not a Number
+    NotANumber,
+    // The value is an integer that doesn't fit in the requested type
+    OutOfRange,
+    // The value has a fractional part and was requested as an integer
+    Fractional,
+    // The value is too large to be represented and would become infinite
+    Overflow,
+    // The value is too small to be represented and would become zero
+    Underflow,
+    // The value has no exact f64 and would be rounded to the nearest one
+    Inexact,
+}
+
+// The exact value of a number literal: `mantissa * 10^exponent`. The
+// mantissa holds the significant digits without leading or trailing zeros,
+// so equal values always have equal representations. Zero is `"0"` with an
+// exponent of 0, and keeps its sign so `-0` can be told apart.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decimal {
+    pub negative: bool,
+    pub mantissa: String,
+    pub exponent: i64,
+}
+
+impl Decimal {
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == "0"
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+}
+
+// Splits a number literal (as produced by the tokenizer) into its exact
+// decimal value
+pub fn decimal(raw: &str) -> Result<Decimal, NumberError> {
+    let (negative, rest) = match raw.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, raw),
+    };
+    let (significand, exp) = match rest.find(['e', 'E']) {
+        Some(i) => (&rest[..i], &rest[i + 1..]),
+        None => (rest, ""),
+    };
+    let (int, frac) = match significand.find('.') {
+        Some(i) => (&significand[..i], &significand[i + 1..]),
+        None => (significand, ""),
+    };
+
+    let digits = format!("{}{}", int, frac);
+    let digits = digits.trim_start_matches('0');
+    let trimmed = digits.trim_end_matches('0');
+
+    if trimmed.is_empty() {
+        return Ok(Decimal {
+            negative,
+            mantissa: "0".to_string(),
+            exponent: 0,
+        });
+    }
+
+    // An exponent too long for an i64 can only make the value infinite or
+    // zero, depending on its sign
+    let exp_negative = exp.starts_with('-');
+    let exp_value = if exp.is_empty() {
+        Some(0)
+    } else {
+        exp.trim_start_matches(['+', '-'])
+            .parse::<i64>()
+            .ok()
+            .map(|e| if exp_negative { -e } else { e })
+    };
+    let out_of_range = if exp_negative {
+        NumberError::Underflow
+    } else {
+        NumberError::Overflow
+    };
+
+    let exponent = exp_value
+        .and_then(|e| e.checked_sub(frac.len() as i64))
+        .and_then(|e| e.checked_add((digits.len() - trimmed.len()) as i64))
+        .ok_or(out_of_range)?;
+
+    Ok(Decimal {
+        negative,
+        mantissa: trimmed.to_string(),
+        exponent,
+    })
+}
+
+pub fn as_i128(raw: &str) -> Result<i128, NumberError> {
+    let decimal = decimal(raw)?;
+    if decimal.is_zero() {
+        return Ok(0);
+    }
+    if !decimal.is_integer() {
+        return Err(NumberError::Fractional);
+    }
+    // i128 has at most 39 digits
+    if decimal.mantissa.len() as i64 + decimal.exponent > 39 {
+        return Err(NumberError::OutOfRange);
+    }
+
+    // Accumulate towards the sign of the result so i128::MIN fits
+    let sign = if decimal.negative { -1 } else { 1 };
+    let digits = decimal
+        .mantissa
+        .bytes()
+        .map(|d| i128::from(d - b'0'))
+        .chain((0..decimal.exponent).map(|_| 0));
+    let mut value: i128 = 0;
+    for digit in digits {
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add(sign * digit))
+            .ok_or(NumberError::OutOfRange)?;
+    }
+    Ok(value)
+}
+
+pub fn as_i64(raw: &str) -> Result<i64, NumberError> {
+    as_i128(raw).and_then(|value| i64::try_from(value).map_err(|_| NumberError::OutOfRange))
+}
+
+pub fn as_u64(raw: &str) -> Result<u64, NumberError> {
+    as_i128(raw).and_then(|value| u64::try_from(value).map_err(|_| NumberError::OutOfRange))
+}
+
+// The nearest f64, or an error if the value can't be told apart from
+// infinity or zero
+pub fn as_f64_rounded(raw: &str) -> Result<f64, NumberError> {
+    let value = raw.parse::<f64>().map_err(|_| NumberError::NotANumber)?;
+    if value.is_infinite() {
+        return Err(NumberError::Overflow);
+    }
+    if value == 0.0 && !decimal(raw)?.is_zero() {
+        return Err(NumberError::Underflow);
+    }
+    Ok(value)
+}
+
+// The f64 with exactly the value of the literal, so `0.1` or
+// `9007199254740993` are Inexact rather than silently rounded
+pub fn as_f64(raw: &str) -> Result<f64, NumberError> {
+    let value = as_f64_rounded(raw)?;
+    if value == 0.0 {
+        return Ok(value);
+    }
+
+    // A double is `m * 2^e`, which takes exactly `-e` digits after the
+    // point once the trailing zero bits of `m` are dropped, so printing
+    // that many digits gives its exact decimal value
+    let bits = value.to_bits();
+    let biased = ((bits >> 52) & 0x7ff) as i64;
+    let fraction = bits & ((1 << 52) - 1);
+    let (m, e) = if biased == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | 1 << 52, biased - 1075)
+    };
+    let places = (-(e + i64::from(m.trailing_zeros()))).max(0) as usize;
+    let printed = decimal(&format!("{:.*}", places, value))?;
+    if printed == decimal(raw)? {
+        Ok(value)
+    } else {
+        Err(NumberError::Inexact)
+    }
+}
@@ -1,5 +1,6 @@
 use types::{ArrayError, ArrayStatus, Identifier, Node, Parsed, Position, Property, PropertyStatus,
-            Token, TokenType};
+            Span, Token, TokenType};
+use strings;
 
 use std::iter::{Iterator, Peekable};
 
@@ -34,17 +35,18 @@ where
     It: Iterator<Item = &'a Token>,
 {
     let mut state = PropertyStates::Start;
-    let start = tokens.peek().unwrap().start;
+    let start = tokens.peek().unwrap().span.start;
 
     while let Some(&token) = tokens.peek() {
         match state {
             PropertyStates::Start => {
                 if let TokenType::String = token.kind {
                     tokens.next();
+                    let raw = token.clone().value.unwrap();
                     state = PropertyStates::Colon(Identifier {
-                        raw: token.clone().value.unwrap(),
-                        start: token.start,
-                        end: token.end,
+                        status: strings::validate(&raw, token.span.start),
+                        raw,
+                        span: token.span,
                     });
                 } else {
                     return None;
@@ -67,8 +69,7 @@ where
                         status: PropertyStatus::Valid,
                         key,
                         value,
-                        start,
-                        end,
+                        span: Span { start, end },
                     });
                 } else {
                     return None;
@@ -87,7 +88,7 @@ where
 {
     let mut state = ObjectStates::Start;
     let mut children: Vec<Property> = vec![];
-    let start = tokens.peek().unwrap().start;
+    let start = tokens.peek().unwrap().span.start;
 
     while let Some(&token) = tokens.peek() {
         match state {
@@ -103,7 +104,7 @@ where
             ObjectStates::OpenObject => {
                 if let TokenType::RightBrace = token.kind {
                     tokens.next();
-                    state = ObjectStates::Done(token.end);
+                    state = ObjectStates::Done(token.span.end);
                 } else {
                     let val = parse_property(tokens);
                     // TODO: remove unwrap
@@ -129,7 +130,7 @@ where
                 // Closed object
                 TokenType::RightBrace => {
                     tokens.next();
-                    state = ObjectStates::Done(token.end);
+                    state = ObjectStates::Done(token.span.end);
                 }
                 // Missing comma between properties
                 TokenType::String => {
@@ -138,8 +139,7 @@ where
                         status: PropertyStatus::MissingComma,
                         key: child.key,
                         value: child.value,
-                        start: child.start,
-                        end: child.end,
+                        span: child.span,
                     });
                     state = ObjectStates::Comma;
                 }
@@ -163,11 +163,10 @@ where
                         status: PropertyStatus::TrailingComma,
                         key: child.key,
                         value: child.value,
-                        start: child.start,
-                        end: child.end,
+                        span: child.span,
                     });
                     tokens.next();
-                    state = ObjectStates::Done(token.end);
+                    state = ObjectStates::Done(token.span.end);
                 } else {
                     panic!("Expected Right brace");
                 }
@@ -181,8 +180,7 @@ where
     if let ObjectStates::Done(end) = state {
         return Some(Node::Object {
             children,
-            start,
-            end,
+            span: Span { start, end },
         });
     }
 
@@ -198,7 +196,7 @@ where
     let mut state = ArrayStates::Start;
     let mut children: Vec<Node> = vec![];
     let mut errors: Vec<ArrayError> = vec![];
-    let start = tokens.peek().unwrap().start;
+    let start = tokens.peek().unwrap().span.start;
 
     while let Some(&token) = tokens.peek() {
         match state {
@@ -214,7 +212,7 @@ where
             ArrayStates::OpenArray => {
                 if let TokenType::RightBracket = token.kind {
                     tokens.next();
-                    state = ArrayStates::Done(token.end);
+                    state = ArrayStates::Done(token.span.end);
                 } else {
                     // TODO: remove unwrap
                     let val = inner_parse_value(tokens).unwrap();
@@ -226,7 +224,7 @@ where
             ArrayStates::Node(position) => match token.kind {
                 TokenType::RightBracket => {
                     tokens.next();
-                    state = ArrayStates::Done(token.end);
+                    state = ArrayStates::Done(token.span.end);
                 }
                 TokenType::Comma => {
                     tokens.next();
@@ -242,15 +240,18 @@ where
                     errors.push(ArrayError::MissingComma(position));
                     state = ArrayStates::Comma;
                 }
-                TokenType::RightBrace | TokenType::Colon => {
+                TokenType::RightBrace
+                | TokenType::Colon
+                | TokenType::LineComment
+                | TokenType::BlockComment => {
                     panic!("Unexpected tokens");
                 }
             },
             ArrayStates::Comma => match token.kind {
                 TokenType::RightBracket => {
                     tokens.next();
-                    errors.push(ArrayError::TrailingComma(token.end));
-                    state = ArrayStates::Done(token.end);
+                    errors.push(ArrayError::TrailingComma(token.span.end));
+                    state = ArrayStates::Done(token.span.end);
                 }
                 _ => {
                     let val = inner_parse_value(tokens).unwrap();
@@ -266,19 +267,23 @@ where
     }
 
     if let ArrayStates::Done(position) = state {
-        if errors.len() == 0 {
+        if errors.is_empty() {
             return Some(Node::Array {
                 status: ArrayStatus::Valid,
                 children,
-                start,
-                end: position,
+                span: Span {
+                    start,
+                    end: position,
+                },
             });
         } else {
             return Some(Node::Array {
                 status: ArrayStatus::Invalid(errors),
                 children,
-                start,
-                end: position,
+                span: Span {
+                    start,
+                    end: position,
+                },
             });
         }
     }
@@ -293,32 +298,34 @@ where
 {
     match tokens.next() {
         Some(token) => match token.kind {
-            TokenType::String => Some(Node::String {
-                raw: token.clone().value.unwrap(),
-                start: token.start,
-                end: token.end,
-            }),
+            TokenType::String => {
+                let raw = token.clone().value.unwrap();
+                Some(Node::String {
+                    status: strings::validate(&raw, token.span.start),
+                    raw,
+                    span: token.span,
+                })
+            }
             TokenType::Number => Some(Node::Number {
                 raw: token.clone().value.unwrap(),
-                start: token.start,
-                end: token.end,
+                span: token.span,
             }),
             TokenType::True | TokenType::False => Some(Node::Boolean {
                 raw: token.clone().value.unwrap(),
-                start: token.start,
-                end: token.end,
+                span: token.span,
             }),
             TokenType::Null => Some(Node::Null {
                 raw: token.clone().value.unwrap(),
-                start: token.start,
-                end: token.end,
+                span: token.span,
             }),
             TokenType::LeftBrace
             | TokenType::RightBrace
             | TokenType::LeftBracket
             | TokenType::RightBracket
             | TokenType::Colon
-            | TokenType::Comma => None,
+            | TokenType::Comma
+            | TokenType::LineComment
+            | TokenType::BlockComment => None,
         },
         None => {
             panic!("expected a literal");
@@ -342,13 +349,15 @@ where
             TokenType::RightBrace
             | TokenType::RightBracket
             | TokenType::Colon
-            | TokenType::Comma => None,
+            | TokenType::Comma
+            | TokenType::LineComment
+            | TokenType::BlockComment => None,
         },
         None => None,
     }
 }
 
-pub fn parse_value(tokens: &Vec<Token>) -> Parsed {
+pub fn parse_value(tokens: &[Token]) -> Parsed {
     let mut iter = tokens.iter().peekable();
     let val = inner_parse_value(&mut iter);
 
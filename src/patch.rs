@@ -0,0 +1,188 @@
+use std::borrow::Cow;
+
+use document::Document;
+use edit::{insert, remove, set, EditError, TextEdit};
+use jsonpath::nodes_equal;
+use pointer;
+use types::{Node, Span};
+
+// Why a JSON Patch couldn't be applied. Spans point into the patch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatchError {
+    // The patch isn't an Array of operations, or an operation has an
+    // unknown `op`, is missing a member it needs, or moves a value into
+    // one of its own children
+    InvalidOperation(Span),
+    // An operation's `path` or `from` can't be edited
+    Edit(Span, EditError),
+    // A `test` operation found a different value
+    TestFailed(Span),
+    // An operation's edits left text that no longer parses, such as when
+    // `src` wasn't the text `root` was parsed from
+    Unparsable(Span),
+}
+
+// Parses edited text, which may no longer be valid. `Document::parse`
+// reports every error rather than panicking on some as `parse` still does,
+// and gives the same tree.
+pub fn reparse(text: &str) -> Option<Node> {
+    Document::parse(text).ok().map(|document| document.to_node(document.root()))
+}
+
+// The text and tree as operations are applied one after another, with the
+// edits made so far kept as edits against the original text
+struct Patched<'a> {
+    original: &'a str,
+    text: String,
+    tree: Node,
+    // Sorted, and never overlapping or touching
+    changes: Vec<TextEdit>,
+}
+
+impl<'a> Patched<'a> {
+    // Applies an edit to the current text, merging it with the changes it
+    // overlaps or touches
+    fn apply(&mut self, edit: &TextEdit) {
+        // Where each change is in the current text, and how far the text
+        // before it has moved
+        let mut ranges = Vec::with_capacity(self.changes.len());
+        let mut shift = 0;
+        for change in &self.changes {
+            let start = change.start as isize + shift;
+            ranges.push((start, start + change.new_text.len() as isize, shift));
+            shift += growth(change);
+        }
+
+        let (edit_start, edit_end) = (edit.start as isize, edit.end as isize);
+        let first = ranges.iter().take_while(|range| range.1 < edit_start).count();
+        let last = first + ranges[first..].iter().take_while(|range| range.0 <= edit_end).count();
+        let mut start = edit_start;
+        let mut end = edit_end;
+        if first < last {
+            start = start.min(ranges[first].0);
+            end = end.max(ranges[last - 1].1);
+        }
+        // The text outside the merged range is unchanged, so maps back to the
+        // original by the shift on either side
+        let original_start = (start - ranges.get(first).map_or(shift, |range| range.2)) as usize;
+        let original_end = (end - ranges.get(last).map_or(shift, |range| range.2)) as usize;
+
+        self.text.replace_range(edit.start..edit.end, &edit.new_text);
+        let new_end = (end + growth(edit)) as usize;
+        let merged = TextEdit::new(original_start, original_end, &self.text[start as usize..new_end]);
+        let unchanged = merged.new_text == self.original[original_start..original_end];
+        self.changes.splice(first..last, if unchanged { None } else { Some(merged) });
+    }
+
+    fn edit(
+        &mut self,
+        edits: Result<Vec<TextEdit>, EditError>,
+        operation: &Node,
+    ) -> Result<(), PatchError> {
+        let mut edits = edits.map_err(|error| PatchError::Edit(operation.span(), error))?;
+        if edits.is_empty() {
+            return Ok(());
+        }
+        // From the end, so each edit leaves the offsets of the rest alone
+        edits.sort_by_key(|edit| edit.start);
+        for edit in edits.iter().rev() {
+            self.apply(edit);
+        }
+        self.tree = reparse(&self.text).ok_or_else(|| PatchError::Unparsable(operation.span()))?;
+        Ok(())
+    }
+
+    fn resolve(&self, path: &str, operation: &Node) -> Result<Node, PatchError> {
+        let error = if pointer::tokens(path).is_none() {
+            EditError::InvalidPointer
+        } else {
+            EditError::NotFound
+        };
+        self.tree
+            .pointer(path)
+            .cloned()
+            .ok_or_else(|| PatchError::Edit(operation.span(), error))
+    }
+}
+
+// How much longer an edit makes the text
+fn growth(edit: &TextEdit) -> isize {
+    edit.new_text.len() as isize - (edit.end - edit.start) as isize
+}
+
+fn member<'p>(operation: &'p Node, key: &str) -> Result<&'p Node, PatchError> {
+    operation
+        .get(key)
+        .ok_or_else(|| PatchError::InvalidOperation(operation.span()))
+}
+
+fn string<'p>(operation: &'p Node, key: &str) -> Result<Cow<'p, str>, PatchError> {
+    let node = member(operation, key)?;
+    node.decoded()
+        .ok_or_else(|| PatchError::InvalidOperation(node.span()))
+}
+
+// Applies a JSON Patch (RFC 6902) to `root`, which was parsed from `src`.
+// Returns the patched tree along with edits against `src` that give its
+// text: each operation is made with `edit::set`, `edit::insert` or
+// `edit::remove`, so everything the patch doesn't touch keeps its
+// formatting and comments. Operations are applied in order and the first
+// that fails stops the patch.
+pub fn apply_patch(src: &str, root: &Node, patch: &Node) -> Result<(Node, Vec<TextEdit>), PatchError> {
+    let operations = match *patch {
+        Node::Array { ref children, .. } => children,
+        _ => return Err(PatchError::InvalidOperation(patch.span())),
+    };
+
+    let mut patched = Patched {
+        original: src,
+        text: src.to_string(),
+        tree: root.clone(),
+        changes: vec![],
+    };
+    for operation in operations {
+        let op = string(operation, "op")?;
+        let path = string(operation, "path")?;
+        match &*op {
+            "add" => {
+                let edits = insert(&patched.text, &patched.tree, &path, member(operation, "value")?);
+                patched.edit(edits, operation)?;
+            }
+            "remove" => {
+                let edits = remove(&patched.text, &patched.tree, &path);
+                patched.edit(edits, operation)?;
+            }
+            "replace" => {
+                patched.resolve(&path, operation)?;
+                let edits = set(&patched.text, &patched.tree, &path, member(operation, "value")?);
+                patched.edit(edits, operation)?;
+            }
+            "move" => {
+                let from = string(operation, "from")?;
+                if from == path {
+                    continue;
+                }
+                if path.starts_with(&format!("{}/", from)) {
+                    return Err(PatchError::InvalidOperation(operation.span()));
+                }
+                let value = patched.resolve(&from, operation)?;
+                let edits = remove(&patched.text, &patched.tree, &from);
+                patched.edit(edits, operation)?;
+                let edits = insert(&patched.text, &patched.tree, &path, &value);
+                patched.edit(edits, operation)?;
+            }
+            "copy" => {
+                let value = patched.resolve(&string(operation, "from")?, operation)?;
+                let edits = insert(&patched.text, &patched.tree, &path, &value);
+                patched.edit(edits, operation)?;
+            }
+            "test" => {
+                if !nodes_equal(&patched.resolve(&path, operation)?, member(operation, "value")?) {
+                    return Err(PatchError::TestFailed(operation.span()));
+                }
+            }
+            _ => return Err(PatchError::InvalidOperation(member(operation, "op")?.span())),
+        }
+    }
+    Ok((patched.tree, patched.changes))
+}
@@ -0,0 +1,123 @@
+use std::fmt;
+use std::ptr;
+
+use types::Node;
+
+// One step from a node to one of its children
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    // The decoded key of an Object member
+    Key(String),
+    Index(usize),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PathSegment::Key(ref key) => write!(f, "{}", escape(key)),
+            PathSegment::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+// Escapes a key for use as a JSON Pointer reference token (RFC 6901)
+pub fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(token: &str) -> Option<String> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c == '~' {
+            match chars.next() {
+                Some('0') => out.push('~'),
+                Some('1') => out.push('/'),
+                _ => return None,
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    Some(out)
+}
+
+// Splits a JSON Pointer into its unescaped reference tokens, or None if it
+// isn't a valid pointer
+pub fn tokens(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(vec![]);
+    }
+    if !pointer.starts_with('/') {
+        return None;
+    }
+    pointer[1..].split('/').map(unescape).collect()
+}
+
+pub fn to_pointer(path: &[PathSegment]) -> String {
+    path.iter().map(|segment| format!("/{}", segment)).collect()
+}
+
+// Array indexes are decimal without leading zeros. `-` (the element after
+// the last one) never refers to an existing node.
+pub fn array_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+pub fn resolve<'a>(node: &'a Node, pointer: &str) -> Option<&'a Node> {
+    let mut current = node;
+    for token in tokens(pointer)? {
+        current = match *current {
+            Node::Object { .. } => current.get(&token)?,
+            Node::Array { .. } => current.get_index(array_index(&token)?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn find_path(node: &Node, target: &Node, path: &mut Vec<PathSegment>) -> bool {
+    if ptr::eq(node, target) {
+        return true;
+    }
+
+    match *node {
+        Node::Object { ref children, .. } => {
+            for property in children {
+                path.push(PathSegment::Key(property.key.decoded().into_owned()));
+                if find_path(&property.value, target, path) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        Node::Array { ref children, .. } => {
+            for (i, child) in children.iter().enumerate() {
+                path.push(PathSegment::Index(i));
+                if find_path(child, target, path) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    false
+}
+
+// The path from `root` to `target`, which must be a reference into the tree
+// under `root` rather than an equal node
+pub fn path_of(root: &Node, target: &Node) -> Option<Vec<PathSegment>> {
+    let mut path = vec![];
+    if find_path(root, target, &mut path) {
+        Some(path)
+    } else {
+        None
+    }
+}
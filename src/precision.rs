@@ -0,0 +1,100 @@
+use number::{self, NumberError};
+use types::{Node, Span};
+use visit::Visitor;
+
+// Integers up to 2^53 in either direction are all exact doubles, past that
+// some are skipped. 2^53 itself is exact, unlike Number.isSafeInteger
+// which stops one short.
+const MAX_EXACT_INTEGER: i128 = 1 << 53;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PrecisionIssue {
+    // An integer beyond ±2^53
+    UnsafeInteger,
+    // A value whose closest f64 doesn't print back as the same number
+    Inexact,
+    // A value that becomes infinite
+    Overflow,
+    // A non-zero value that becomes zero
+    Underflow,
+}
+
+#[derive(Clone, Debug)]
+pub struct PrecisionWarning {
+    pub issue: PrecisionIssue,
+    pub raw: String,
+    // What a consumer parsing the number as an IEEE-754 double will see
+    pub value: f64,
+    pub span: Span,
+}
+
+fn check_number(raw: &str) -> Option<(PrecisionIssue, f64)> {
+    let value = match number::as_f64_rounded(raw) {
+        Ok(value) => value,
+        Err(NumberError::Overflow) => {
+            let infinity = if raw.starts_with('-') {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            };
+            return Some((PrecisionIssue::Overflow, infinity));
+        }
+        Err(NumberError::Underflow) => {
+            let zero = if raw.starts_with('-') { -0.0 } else { 0.0 };
+            return Some((PrecisionIssue::Underflow, zero));
+        }
+        Err(_) => return None,
+    };
+
+    // Only numbers written as integers are treated as integers, `1.5e300`
+    // is fine as long as it survives the round trip below
+    if !raw.contains(['.', 'e', 'E']) {
+        match number::as_i128(raw) {
+            Ok(n) if !(-MAX_EXACT_INTEGER..=MAX_EXACT_INTEGER).contains(&n) => {
+                return Some((PrecisionIssue::UnsafeInteger, value))
+            }
+            Err(NumberError::OutOfRange) => return Some((PrecisionIssue::UnsafeInteger, value)),
+            _ => {}
+        }
+    }
+
+    // Rust prints the shortest digits that parse back to the same f64, the
+    // same as JavaScript does, so anything else would be rewritten
+    let printed = number::decimal(&format!("{:e}", value)).ok();
+    let exact = number::decimal(raw).ok();
+    match (printed, exact) {
+        (Some(ref printed), Some(ref exact))
+            if printed.mantissa != exact.mantissa || printed.exponent != exact.exponent =>
+        {
+            Some((PrecisionIssue::Inexact, value))
+        }
+        _ => None,
+    }
+}
+
+struct PrecisionCheck {
+    warnings: Vec<PrecisionWarning>,
+}
+
+impl Visitor for PrecisionCheck {
+    fn visit_number(&mut self, node: &Node) {
+        if let Node::Number { ref raw, span } = *node {
+            if let Some((issue, value)) = check_number(raw) {
+                self.warnings.push(PrecisionWarning {
+                    issue,
+                    raw: raw.clone(),
+                    value,
+                    span,
+                });
+            }
+        }
+    }
+}
+
+// Finds every number that a consumer using IEEE-754 doubles (such as
+// JavaScript) would not read back exactly
+pub fn check_precision(node: &Node) -> Vec<PrecisionWarning> {
+    let mut check = PrecisionCheck { warnings: vec![] };
+    check.visit_node(node);
+    check.warnings
+}
@@ -0,0 +1,261 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use edit::{apply_edits, matches_source, EditError, Extent, Source, TextEdit};
+use types::{Node, Property, Span};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyOrder {
+    // By Unicode code point
+    Alphabetical,
+    // Ignoring case, with runs of digits compared by their value, so
+    // `item2` comes before `item10`
+    Natural,
+    // The listed keys first, in the order given, then the rest
+    // alphabetically
+    Custom(Vec<String>),
+}
+
+#[derive(Clone, Debug)]
+pub struct SortOptions {
+    pub order: KeyOrder,
+    // Sort the Objects inside the one sorted too, including those in Arrays
+    pub recursive: bool,
+}
+
+impl Default for SortOptions {
+    fn default() -> SortOptions {
+        SortOptions {
+            order: KeyOrder::Alphabetical,
+            recursive: true,
+        }
+    }
+}
+
+// Splits text into runs of digits and runs of anything else
+fn runs(text: &str) -> Vec<&str> {
+    let mut runs = vec![];
+    let mut start = 0;
+    for (i, c) in text.char_indices().skip(1) {
+        let previous = text[..i].chars().next_back().unwrap();
+        if c.is_ascii_digit() != previous.is_ascii_digit() {
+            runs.push(&text[start..i]);
+            start = i;
+        }
+    }
+    if start < text.len() {
+        runs.push(&text[start..]);
+    }
+    runs
+}
+
+// A key decoded once, with what its order compares
+struct Key<'a> {
+    text: Cow<'a, str>,
+    // For the natural order, its runs lowercased
+    runs: Vec<String>,
+    // For a custom order, its place in the list
+    rank: usize,
+}
+
+impl<'a> Key<'a> {
+    fn new(order: &KeyOrder, property: &'a Property) -> Key<'a> {
+        let text = property.key.decoded();
+        let (runs, rank) = match *order {
+            KeyOrder::Alphabetical => (vec![], 0),
+            KeyOrder::Natural => (runs(&text).into_iter().map(str::to_lowercase).collect(), 0),
+            KeyOrder::Custom(ref keys) => {
+                (vec![], keys.iter().position(|key| *key == text).unwrap_or(keys.len()))
+            }
+        };
+        Key { text, runs, rank }
+    }
+}
+
+fn keys<'a>(order: &KeyOrder, children: &'a [Property]) -> Vec<Key<'a>> {
+    children.iter().map(|property| Key::new(order, property)).collect()
+}
+
+fn natural_cmp(a: &[String], b: &[String]) -> Ordering {
+    let ordering = a.iter().zip(b).map(|(a, b)| {
+        if a.as_bytes()[0].is_ascii_digit() && b.as_bytes()[0].is_ascii_digit() {
+            let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+            a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+        } else {
+            a.cmp(b)
+        }
+    });
+    ordering
+        .fold(Ordering::Equal, Ordering::then)
+        .then_with(|| a.len().cmp(&b.len()))
+}
+
+fn compare(order: &KeyOrder, a: &Key, b: &Key) -> Ordering {
+    match *order {
+        KeyOrder::Alphabetical => a.text.cmp(&b.text),
+        KeyOrder::Natural => natural_cmp(&a.runs, &b.runs).then_with(|| a.text.cmp(&b.text)),
+        KeyOrder::Custom(_) => a.rank.cmp(&b.rank).then_with(|| a.text.cmp(&b.text)),
+    }
+}
+
+fn in_order(order: &KeyOrder, a: &Key, b: &Key) -> bool {
+    compare(order, a, b) != Ordering::Greater
+}
+
+// A member as it moves to another slot: everything but its comma
+struct Piece {
+    // From its leading comments to the end of its value
+    member: String,
+    // Any comments after it
+    after: String,
+}
+
+struct Sorter<'a> {
+    source: Source<'a>,
+    src: &'a str,
+    options: &'a SortOptions,
+}
+
+impl<'a> Sorter<'a> {
+    fn children(&self, node: &'a Node) -> Vec<&'a Node> {
+        match *node {
+            Node::Object { ref children, .. } if self.options.recursive => {
+                children.iter().map(|property| &property.value).collect()
+            }
+            Node::Array { ref children, .. } if self.options.recursive => children.iter().collect(),
+            _ => vec![],
+        }
+    }
+
+    // The text of a node with the Objects inside it sorted
+    fn text(&self, node: &'a Node) -> Result<String, EditError> {
+        let span = node.span();
+        let mut edits = vec![];
+        self.collect(node, &mut edits)?;
+        for edit in &mut edits {
+            edit.start -= span.start.index;
+            edit.end -= span.start.index;
+        }
+        apply_edits(&self.src[span.start.index..span.end.index], &edits)
+    }
+
+    fn collect(&self, node: &'a Node, edits: &mut Vec<TextEdit>) -> Result<(), EditError> {
+        let children = match *node {
+            Node::Object { ref children, .. } => children,
+            _ => {
+                for child in self.children(node) {
+                    self.collect(child, edits)?;
+                }
+                return Ok(());
+            }
+        };
+        let order = &self.options.order;
+        let keys = keys(order, children);
+        if keys.windows(2).all(|pair| in_order(order, &pair[0], &pair[1])) {
+            for child in self.children(node) {
+                self.collect(child, edits)?;
+            }
+            return Ok(());
+        }
+
+        // Each member's slot runs from its leading comments to its trailing
+        // ones. Members move between slots with their comments, while the
+        // commas and the whitespace between slots stay where they are.
+        let extents: Vec<Extent> = children
+            .iter()
+            .map(|property| self.source.extent(property.span))
+            .collect();
+        let pieces: Vec<Piece> = children
+            .iter()
+            .zip(&extents)
+            .map(|(property, extent)| {
+                let (span, value) = (property.span, property.value.span());
+                let after = match extent.comma {
+                    Some((start, end)) => {
+                        format!("{}{}", &self.src[span.end.index..start], &self.src[end..extent.end])
+                    }
+                    None => self.src[span.end.index..extent.end].to_string(),
+                };
+                let text = if self.options.recursive {
+                    self.text(&property.value)?
+                } else {
+                    self.src[value.start.index..value.end.index].to_string()
+                };
+                Ok(Piece {
+                    member: format!(
+                        "{}{}{}",
+                        &self.src[extent.start..value.start.index],
+                        text,
+                        &self.src[value.end.index..span.end.index]
+                    ),
+                    after,
+                })
+            })
+            .collect::<Result<_, EditError>>()?;
+
+        let mut sorted: Vec<usize> = (0..children.len()).collect();
+        sorted.sort_by(|&a, &b| compare(order, &keys[a], &keys[b]));
+
+        let mut text = String::new();
+        for (slot, &i) in sorted.iter().enumerate() {
+            if slot > 0 {
+                text.push_str(&self.src[extents[slot - 1].end..extents[slot].start]);
+            }
+            text.push_str(&pieces[i].member);
+            if extents[slot].comma.is_some() {
+                text.push(',');
+            }
+            text.push_str(&pieces[i].after);
+        }
+        edits.push(TextEdit {
+            start: extents[0].start,
+            end: extents[extents.len() - 1].end,
+            new_text: text,
+        });
+        Ok(())
+    }
+}
+
+// Sorts the keys of an Object, returning edits to `src`, which `node` was
+// parsed from. Members move with their comments and keep their own
+// formatting; commas stay where they were. Members with the same key keep
+// their order. Returns no edits when the keys are already sorted, and
+// SourceMismatch when `src` isn't the text of `node`.
+pub fn sort_keys(src: &str, node: &Node, options: &SortOptions) -> Result<Vec<TextEdit>, EditError> {
+    if !matches_source(src, node) {
+        return Err(EditError::SourceMismatch);
+    }
+    let sorter = Sorter {
+        source: Source::new(src),
+        src,
+        options,
+    };
+    let mut edits = vec![];
+    sorter.collect(node, &mut edits)?;
+    Ok(edits)
+}
+
+// The span of the first key, in document order, that `sort_keys` would
+// move, or None if the keys are sorted
+pub fn check_key_order(node: &Node, options: &SortOptions) -> Option<Span> {
+    match *node {
+        Node::Object { ref children, .. } => {
+            let keys = keys(&options.order, children);
+            for (i, property) in children.iter().enumerate() {
+                if i > 0 && !in_order(&options.order, &keys[i - 1], &keys[i]) {
+                    return Some(property.key.span);
+                }
+                if options.recursive {
+                    if let Some(span) = check_key_order(&property.value, options) {
+                        return Some(span);
+                    }
+                }
+            }
+            None
+        }
+        Node::Array { ref children, .. } if options.recursive => {
+            children.iter().find_map(|child| check_key_order(child, options))
+        }
+        _ => None,
+    }
+}
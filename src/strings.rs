@@ -0,0 +1,268 @@
+use std::borrow::Cow;
+
+use types::{Position, Span, StringError, StringStatus};
+
+// A single decoded character. Escapes that could not be decoded still
+// produce a segment so callers can keep going, with `error` describing what
+// was wrong: lone surrogates and broken `\u` escapes decode to U+FFFD, any
+// other unknown escape decodes to the escaped character itself.
+#[derive(Clone, Debug)]
+pub struct Segment {
+    pub value: char,
+    pub start: Position,
+    pub end: Position,
+    pub error: Option<StringError>,
+}
+
+// Walks the contents of a string token (everything between the quotes, with
+// escapes left as written) one decoded character at a time.
+pub struct Segments<'a> {
+    raw: &'a [u8],
+    text: &'a str,
+    i: usize,
+    position: Position,
+}
+
+impl<'a> Segments<'a> {
+    // `start` is the position of the opening quote
+    pub fn new(raw: &'a str, start: Position) -> Segments<'a> {
+        Segments {
+            raw: raw.as_bytes(),
+            text: raw,
+            i: 0,
+            position: Position {
+                line: start.line,
+                column: start.column + 1,
+                index: start.index + 1,
+            },
+        }
+    }
+
+    fn advance(&mut self, len: usize) {
+        for _ in 0..len {
+            if self.raw[self.i] == b'\n' {
+                self.position.line += 1;
+                self.position.column = 1;
+            } else {
+                self.position.column += 1;
+            }
+            self.position.index += 1;
+            self.i += 1;
+        }
+    }
+
+    fn hex_digits(&self, from: usize) -> usize {
+        self.raw[from..]
+            .iter()
+            .take(4)
+            .take_while(|c| c.is_ascii_hexdigit())
+            .count()
+    }
+
+    // Reads the code unit of a complete `\uXXXX` escape starting at `from`
+    fn code_unit(&self, from: usize) -> Option<u32> {
+        if self.raw.len() < from + 6 || &self.raw[from..from + 2] != b"\\u" {
+            return None;
+        }
+        if self.hex_digits(from + 2) != 4 {
+            return None;
+        }
+        u32::from_str_radix(&self.text[from + 2..from + 6], 16).ok()
+    }
+
+    fn unicode_escape(&mut self) -> (char, usize, Option<StringError>) {
+        let unit = match self.code_unit(self.i) {
+            Some(unit) => unit,
+            None => {
+                let len = 2 + self.hex_digits(self.i + 2);
+                return (
+                    '\u{FFFD}',
+                    len,
+                    Some(StringError::InvalidUnicodeEscape(self.position)),
+                );
+            }
+        };
+
+        match unit {
+            0xD800..=0xDBFF => match self.code_unit(self.i + 6) {
+                Some(low @ 0xDC00..=0xDFFF) => {
+                    let c = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+                    (::std::char::from_u32(c).unwrap(), 12, None)
+                }
+                _ => (
+                    '\u{FFFD}',
+                    6,
+                    Some(StringError::LoneSurrogate(self.position)),
+                ),
+            },
+            0xDC00..=0xDFFF => (
+                '\u{FFFD}',
+                6,
+                Some(StringError::LoneSurrogate(self.position)),
+            ),
+            _ => (::std::char::from_u32(unit).unwrap(), 6, None),
+        }
+    }
+
+    fn escape(&mut self) -> (char, usize, Option<StringError>) {
+        let c = match self.text[self.i + 1..].chars().next() {
+            Some(c) => c,
+            // The tokenizer never ends a string on a backslash
+            None => return ('\\', 1, Some(StringError::InvalidEscape(self.position))),
+        };
+
+        match c {
+            '"' => ('"', 2, None),
+            '\\' => ('\\', 2, None),
+            '/' => ('/', 2, None),
+            'b' => ('\u{8}', 2, None),
+            'f' => ('\u{c}', 2, None),
+            'n' => ('\n', 2, None),
+            'r' => ('\r', 2, None),
+            't' => ('\t', 2, None),
+            'u' => self.unicode_escape(),
+            _ => (
+                c,
+                1 + c.len_utf8(),
+                Some(StringError::InvalidEscape(self.position)),
+            ),
+        }
+    }
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Segment;
+
+    fn next(&mut self) -> Option<Segment> {
+        if self.i >= self.raw.len() {
+            return None;
+        }
+
+        let start = self.position;
+        let (value, len, error) = if self.raw[self.i] == b'\\' {
+            self.escape()
+        } else {
+            let c = self.text[self.i..].chars().next().unwrap();
+            if c < '\u{20}' {
+                (c, 1, Some(StringError::ControlCharacter(start)))
+            } else {
+                (c, c.len_utf8(), None)
+            }
+        };
+        self.advance(len);
+
+        Some(Segment {
+            value,
+            start,
+            end: self.position,
+            error,
+        })
+    }
+}
+
+pub fn validate(raw: &str, start: Position) -> StringStatus {
+    let errors: Vec<StringError> = Segments::new(raw, start)
+        .filter_map(|segment| segment.error)
+        .collect();
+
+    if errors.is_empty() {
+        StringStatus::Valid
+    } else {
+        StringStatus::Invalid(errors)
+    }
+}
+
+pub fn decode(raw: &str) -> Cow<'_, str> {
+    if !raw.contains('\\') {
+        return Cow::Borrowed(raw);
+    }
+
+    // Positions are only used for diagnostics, which we don't need here
+    let start = Position {
+        line: 1,
+        column: 0,
+        index: 0,
+    };
+    Cow::Owned(Segments::new(raw, start).map(|segment| segment.value).collect())
+}
+
+// Maps offsets in a decoded string back to where they were written in the
+// document, so problems found in the unescaped value can be reported
+// against the escaped source.
+#[derive(Clone, Debug)]
+pub struct SourceMap {
+    // Decoded byte offset and source start of every decoded char
+    chars: Vec<(usize, Position)>,
+    len: usize,
+    end: Position,
+}
+
+impl SourceMap {
+    // `start` is the position of the opening quote
+    pub fn new(raw: &str, start: Position) -> SourceMap {
+        let mut chars = vec![];
+        let mut len = 0;
+        let mut end = Position {
+            line: start.line,
+            column: start.column + 1,
+            index: start.index + 1,
+        };
+
+        for segment in Segments::new(raw, start) {
+            chars.push((len, segment.start));
+            len += segment.value.len_utf8();
+            end = segment.end;
+        }
+
+        SourceMap { chars, len, end }
+    }
+
+    // Where the char containing the decoded byte `offset` starts. The length
+    // of the decoded string maps to the closing quote.
+    pub fn position_of_byte(&self, offset: usize) -> Option<Position> {
+        if offset == self.len {
+            return Some(self.end);
+        }
+        if offset > self.len {
+            return None;
+        }
+        let i = match self.chars.binary_search_by_key(&offset, |&(o, _)| o) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        Some(self.chars[i].1)
+    }
+
+    // Where the `index`th decoded char starts
+    pub fn position_of_char(&self, index: usize) -> Option<Position> {
+        if index == self.chars.len() {
+            return Some(self.end);
+        }
+        self.chars.get(index).map(|&(_, position)| position)
+    }
+
+    // The source range covering the decoded bytes `start..end`, including
+    // the whole escape of any char that is only partly covered
+    pub fn source_range(&self, start: usize, end: usize) -> Option<Span> {
+        if start > end {
+            return None;
+        }
+        let from = self.position_of_byte(start)?;
+        if end == start {
+            return Some(Span {
+                start: from,
+                end: from,
+            });
+        }
+        let to = match self.chars.binary_search_by_key(&end, |&(o, _)| o) {
+            Ok(i) => self.chars[i].1,
+            Err(i) if i < self.chars.len() => self.chars[i].1,
+            Err(_) if end <= self.len => self.end,
+            Err(_) => return None,
+        };
+        Some(Span {
+            start: from,
+            end: to,
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use types::{TokenType, Position, Token};
+use types::{ParseError, ParseErrorType, TokenType, Position, Span, Token};
 
 enum Escapes {
   Quotation,
@@ -105,16 +105,13 @@ fn parse_whitespace(input: &str, index: usize, line: usize, column: usize) -> Op
 fn parse_char(input: &str, index: usize, line: usize, column: usize) -> Option<Match> {
   let c = input.as_bytes()[index];
 
-  match map_punctuator_tokens(c) {
-    Some(t) => Some(Match {
-      kind: t,
-      line,
-      column: column + 1,
-      index: index + 1,
-      value: None,
-    }),
-    None => None,
-  }
+  map_punctuator_tokens(c).map(|t| Match {
+    kind: t,
+    line,
+    column: column + 1,
+    index: index + 1,
+    value: None,
+  })
 }
 
 fn parse_keyword(input: &str, index: usize, line: usize, column: usize) -> Option<Match> {
@@ -148,17 +145,68 @@ fn parse_keyword(input: &str, index: usize, line: usize, column: usize) -> Optio
   }
 }
 
+// Comments are only valid in JSONC, but are always tokenized so that the
+// parser can report them
+fn parse_comment(input: &str, index: usize, line: usize, column: usize) -> Option<Match> {
+  let rest = &input[index..];
+  if rest.starts_with("//") {
+    let len = rest.find(['\n', '\r']).unwrap_or(rest.len());
+    Some(Match {
+      kind: TokenType::LineComment,
+      line,
+      column: column + len,
+      index: index + len,
+      value: Some(rest[..len].to_string()),
+    })
+  } else if let Some(body) = rest.strip_prefix("/*") {
+    let len = body.find("*/")? + 4;
+    let mut line = line;
+    let mut column = column;
+    for &c in &rest.as_bytes()[..len] {
+      if c == b'\n' {
+        line += 1;
+        column = 1;
+      } else {
+        column += 1;
+      }
+    }
+    Some(Match {
+      kind: TokenType::BlockComment,
+      line,
+      column,
+      index: index + len,
+      value: Some(rest[..len].to_string()),
+    })
+  } else {
+    None
+  }
+}
+
 fn parse_string(input: &str, index: usize, line: usize, column: usize) -> Option<Match> {
   let mut i = index;
+  let mut line = line;
+  let mut column = column;
   let mut buffer = Vec::new();
   let mut state = StringStates::Start;
 
+  // Invalid escapes and control characters are kept in the token and
+  // reported by the parser, so a raw newline has to move us to the next line
+  let mut advance = |c: u8, i: &mut usize| {
+    *i += 1;
+    if c == b'\n' {
+      line += 1;
+      column = 1;
+    } else {
+      column += 1;
+    }
+  };
+
   while i < input.len() {
     let c = input.as_bytes()[i];
     match state {
       StringStates::Start => match c {
         b'"' => {
-          i += 1;
+          advance(c, &mut i);
           state = StringStates::StartQuoteOrChar;
         }
         _ => return None,
@@ -166,47 +214,44 @@ fn parse_string(input: &str, index: usize, line: usize, column: usize) -> Option
       StringStates::StartQuoteOrChar => match c {
         b'\\' => {
           buffer.push(c);
-          i += 1;
+          advance(c, &mut i);
           state = StringStates::Escape;
         }
         b'"' => {
-          i += 1;
+          advance(c, &mut i);
           return Some(Match {
             kind: TokenType::String,
-            line: line,
-            column: column + i - index,
+            line,
+            column,
             index: i,
             value: Some(String::from_utf8(buffer).unwrap()),
           });
         }
         _ => {
-          i += 1;
           buffer.push(c);
+          advance(c, &mut i);
         }
       },
       StringStates::Escape => match map_escapes(c) {
         Some(Escapes::HexadecimalDigits) => {
           buffer.push(c);
-          i += 1;
+          advance(c, &mut i);
           for _ in 0..4 {
-            let ch = input.as_bytes()[i];
-            if ch.is_ascii_hexdigit() {
-              buffer.push(ch);
-              i += 1;
-            } else {
-              return None;
+            match input.as_bytes().get(i) {
+              Some(&ch) if ch.is_ascii_hexdigit() => {
+                buffer.push(ch);
+                advance(ch, &mut i);
+              }
+              _ => break,
             }
           }
           state = StringStates::StartQuoteOrChar;
         }
-        Some(_t) => {
+        _ => {
           buffer.push(c);
-          i += 1;
+          advance(c, &mut i);
           state = StringStates::StartQuoteOrChar;
         }
-        _ => {
-          return None;
-        }
       },
     }
   }
@@ -228,7 +273,7 @@ fn parse_number(input: &str, index: usize, line: usize, column: usize) -> Option
           passed_value_index = i + 1;
           state = NumberStates::Zero;
         }
-        b'1'...b'9' => {
+        b'1'..=b'9' => {
           passed_value_index = i + 1;
           state = NumberStates::Digit;
         }
@@ -240,7 +285,7 @@ fn parse_number(input: &str, index: usize, line: usize, column: usize) -> Option
           passed_value_index = i + 1;
           state = NumberStates::Zero;
         }
-        b'1'...b'9' => {
+        b'1'..=b'9' => {
           passed_value_index = i + 1;
           state = NumberStates::Digit;
         }
@@ -254,14 +299,14 @@ fn parse_number(input: &str, index: usize, line: usize, column: usize) -> Option
       },
 
       NumberStates::Digit => match c {
-        b'0'...b'9' => passed_value_index = i + 1,
+        b'0'..=b'9' => passed_value_index = i + 1,
         b'.' => state = NumberStates::Point,
         b'e' | b'E' => state = NumberStates::Exp,
         _ => break,
       },
 
       NumberStates::Point => match c {
-        b'0'...b'9' => {
+        b'0'..=b'9' => {
           passed_value_index = i + 1;
           state = NumberStates::DigitFraction;
         }
@@ -269,14 +314,14 @@ fn parse_number(input: &str, index: usize, line: usize, column: usize) -> Option
       },
 
       NumberStates::DigitFraction => match c {
-        b'0'...b'9' => passed_value_index = i + 1,
+        b'0'..=b'9' => passed_value_index = i + 1,
         b'e' | b'E' => state = NumberStates::Exp,
         _ => break,
       },
 
       NumberStates::Exp => match c {
         b'+' | b'-' => state = NumberStates::ExpDigitOrSign,
-        b'0'...b'9' => {
+        b'0'..=b'9' => {
           passed_value_index = i + 1;
           state = NumberStates::ExpDigitOrSign;
         }
@@ -284,17 +329,17 @@ fn parse_number(input: &str, index: usize, line: usize, column: usize) -> Option
       },
 
       NumberStates::ExpDigitOrSign => match c {
-        b'0'...b'9' => passed_value_index = i + 1,
+        b'0'..=b'9' => passed_value_index = i + 1,
         _ => break,
       },
     }
     i += 1;
   }
 
-  if passed_value_index > 0 {
+  if passed_value_index > start_index {
     Some(Match {
       kind: TokenType::Number,
-      line: line,
+      line,
       column: column + passed_value_index - start_index,
       index: passed_value_index,
       value: Some(input[start_index..passed_value_index].to_string()),
@@ -304,93 +349,95 @@ fn parse_number(input: &str, index: usize, line: usize, column: usize) -> Option
   }
 }
 
-pub fn tokenize(input: &str) -> Vec<Token> {
-  let mut line: usize = 1;
-  let mut column: usize = 1;
-  let mut index: usize = 0;
-  let mut tokens: Vec<Token> = Vec::new();
-
-  while index < input.len() {
-    if let Some(pos) = parse_whitespace(input, index, line, column) {
-      line = pos.line;
-      column = pos.column;
-      index = pos.index;
-    } else if let Some(pos) = parse_char(input, index, line, column) {
-      tokens.push(Token {
-        kind: pos.kind,
-        value: pos.value,
-        start: Position {
-          line,
-          column,
-          index,
-        },
-        end: Position {
-          line: pos.line,
-          column: pos.column,
-          index: pos.index,
-        },
-      });
-      line = pos.line;
-      column = pos.column;
-      index = pos.index;
-    } else if let Some(pos) = parse_keyword(input, index, line, column) {
-      tokens.push(Token {
-        kind: pos.kind,
-        value: pos.value,
-        start: Position {
-          line,
-          column,
-          index,
-        },
-        end: Position {
-          line: pos.line,
-          column: pos.column,
-          index: pos.index,
-        },
-      });
-      line = pos.line;
-      column = pos.column;
-      index = pos.index;
-    } else if let Some(pos) = parse_string(input, index, line, column) {
-      tokens.push(Token {
-        kind: pos.kind,
-        value: pos.value,
-        start: Position {
-          line,
-          column,
-          index,
-        },
-        end: Position {
-          line: pos.line,
-          column: pos.column,
-          index: pos.index,
-        },
-      });
-      line = pos.line;
-      column = pos.column;
-      index = pos.index;
-    } else if let Some(pos) = parse_number(input, index, line, column) {
-      tokens.push(Token {
+// Splits `input` into tokens one at a time, so that large documents can be
+// processed without holding all of their tokens at once. Text that doesn't
+// start any token gives an InvalidToken error, after which there are no
+// more tokens.
+pub struct Tokens<'a> {
+  input: &'a str,
+  index: usize,
+  line: usize,
+  column: usize,
+}
+
+impl<'a> Tokens<'a> {
+  pub fn new(input: &'a str) -> Tokens<'a> {
+    Tokens {
+      input,
+      index: 0,
+      line: 1,
+      column: 1,
+    }
+  }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+  type Item = Result<Token, ParseError>;
+
+  fn next(&mut self) -> Option<Result<Token, ParseError>> {
+    let input = self.input;
+    while self.index < input.len() {
+      let index = self.index;
+      if let Some(pos) = parse_whitespace(input, index, self.line, self.column) {
+        self.line = pos.line;
+        self.column = pos.column;
+        self.index = pos.index;
+        continue;
+      }
+
+      let (line, column) = (self.line, self.column);
+      let pos = parse_char(input, index, line, column)
+        .or_else(|| parse_keyword(input, index, line, column))
+        .or_else(|| parse_string(input, index, line, column))
+        .or_else(|| parse_number(input, index, line, column))
+        .or_else(|| parse_comment(input, index, line, column));
+      let pos = match pos {
+        Some(pos) => pos,
+        None => {
+          self.index = input.len();
+          return Some(Err(ParseError {
+            position: Position {
+              line,
+              column,
+              index,
+            },
+            error_type: ParseErrorType::InvalidToken,
+          }));
+        }
+      };
+
+      self.line = pos.line;
+      self.column = pos.column;
+      self.index = pos.index;
+      return Some(Ok(Token {
         kind: pos.kind,
         value: pos.value,
-        start: Position {
-          line,
-          column,
-          index,
-        },
-        end: Position {
-          line: pos.line,
-          column: pos.column,
-          index: pos.index,
+        span: Span {
+          start: Position {
+            line,
+            column,
+            index,
+          },
+          end: Position {
+            line: pos.line,
+            column: pos.column,
+            index: pos.index,
+          },
         },
-      });
-      line = pos.line;
-      column = pos.column;
-      index = pos.index;
-    } else {
-      panic!("TODO: Errors end up here");
+      }));
     }
+    None
   }
+}
 
-  tokens
+// All the tokens of `input`, or those before the first error along with it
+pub fn tokenize(input: &str) -> (Vec<Token>, Option<ParseError>) {
+  let mut tokens = vec![];
+  for token in Tokens::new(input) {
+    match token {
+      Ok(token) => tokens.push(token),
+      Err(error) => return (tokens, Some(error)),
+    }
+  }
+  (tokens, None)
 }
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use pointer::PathSegment;
+use types::{Identifier, Node, Property};
+
+// A handle to a node in a TreeIndex. Nodes are numbered in document order
+// (pre-order), starting with the root at 0.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub usize);
+
+struct Entry<'a> {
+    node: &'a Node,
+    parent: Option<NodeId>,
+    depth: usize,
+    // Position among the parent's children, and the member when the parent
+    // is an Object
+    index: usize,
+    property: Option<&'a Property>,
+    children: Vec<NodeId>,
+}
+
+// Parent links for a parsed tree, which `Node` itself doesn't keep. Built
+// once in a single pass, after which moving up or sideways is constant time.
+pub struct TreeIndex<'a> {
+    entries: Vec<Entry<'a>>,
+    ids: HashMap<*const Node, NodeId>,
+}
+
+impl<'a> TreeIndex<'a> {
+    pub fn new(root: &'a Node) -> TreeIndex<'a> {
+        let mut index = TreeIndex {
+            entries: vec![],
+            ids: HashMap::new(),
+        };
+        index.add(root, None, 0, None);
+        index
+    }
+
+    fn add(
+        &mut self,
+        node: &'a Node,
+        parent: Option<NodeId>,
+        index: usize,
+        property: Option<&'a Property>,
+    ) -> NodeId {
+        let id = NodeId(self.entries.len());
+        self.entries.push(Entry {
+            node,
+            parent,
+            depth: parent.map_or(0, |parent| self.entries[parent.0].depth + 1),
+            index,
+            property,
+            children: vec![],
+        });
+        self.ids.insert(node as *const Node, id);
+
+        let children: Vec<NodeId> = match *node {
+            Node::Object { ref children, .. } => children
+                .iter()
+                .enumerate()
+                .map(|(i, property)| self.add(&property.value, Some(id), i, Some(property)))
+                .collect(),
+            Node::Array { ref children, .. } => children
+                .iter()
+                .enumerate()
+                .map(|(i, child)| self.add(child, Some(id), i, None))
+                .collect(),
+            _ => vec![],
+        };
+        self.entries[id.0].children = children;
+        id
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Every node in document order
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.entries.len()).map(NodeId)
+    }
+
+    pub fn node(&self, id: NodeId) -> &'a Node {
+        self.entries[id.0].node
+    }
+
+    // The id of a node borrowed from the indexed tree. Equal nodes elsewhere
+    // don't count.
+    pub fn id_of(&self, node: &Node) -> Option<NodeId> {
+        self.ids.get(&(node as *const Node)).cloned()
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.entries[id.0].parent
+    }
+
+    // The number of ancestors, so 0 for the root
+    pub fn depth(&self, id: NodeId) -> usize {
+        self.entries[id.0].depth
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.entries[id.0].children
+    }
+
+    // The position of the node among its parent's elements or members
+    pub fn index_in_parent(&self, id: NodeId) -> Option<usize> {
+        self.entries[id.0].parent.map(|_| self.entries[id.0].index)
+    }
+
+    // The Object member the node is the value of
+    pub fn property(&self, id: NodeId) -> Option<&'a Property> {
+        self.entries[id.0].property
+    }
+
+    pub fn key_in_parent(&self, id: NodeId) -> Option<&'a Identifier> {
+        self.entries[id.0].property.map(|property| &property.key)
+    }
+
+    // The parent, grandparent and so on up to the root
+    pub fn ancestors(&self, id: NodeId) -> Ancestors<'_, 'a> {
+        Ancestors {
+            index: self,
+            next: self.parent(id),
+        }
+    }
+
+    // The other children of the node's parent, in order
+    pub fn siblings(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let siblings = match self.parent(id) {
+            Some(parent) => self.children(parent),
+            None => &[],
+        };
+        siblings.iter().cloned().filter(move |&sibling| sibling != id)
+    }
+
+    pub fn next_sibling(&self, id: NodeId) -> Option<NodeId> {
+        let parent = self.parent(id)?;
+        self.children(parent).get(self.entries[id.0].index + 1).cloned()
+    }
+
+    pub fn prev_sibling(&self, id: NodeId) -> Option<NodeId> {
+        let parent = self.parent(id)?;
+        let index = self.entries[id.0].index;
+        if index == 0 {
+            None
+        } else {
+            Some(self.children(parent)[index - 1])
+        }
+    }
+
+    // The keys and indexes leading from the root to the node
+    pub fn path(&self, id: NodeId) -> Vec<PathSegment> {
+        let mut path = vec![];
+        let mut current = id;
+        while self.parent(current).is_some() {
+            let entry = &self.entries[current.0];
+            path.push(match entry.property {
+                Some(property) => PathSegment::Key(property.key.decoded().into_owned()),
+                None => PathSegment::Index(entry.index),
+            });
+            current = entry.parent.unwrap();
+        }
+        path.reverse();
+        path
+    }
+}
+
+pub struct Ancestors<'i, 'a: 'i> {
+    index: &'i TreeIndex<'a>,
+    next: Option<NodeId>,
+}
+
+impl<'i, 'a> Iterator for Ancestors<'i, 'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.next?;
+        self.next = self.index.parent(id);
+        Some(id)
+    }
+}
@@ -1,4 +1,14 @@
-#[derive(Copy, Clone, Debug)]
+use std::borrow::Cow;
+use std::ops::Index;
+
+use document::NodeKind;
+use jsonpath::{self, JsonPathError, QueryMatch};
+use location::{self, Location};
+use number::{self, Decimal, NumberError};
+use pointer::{self, PathSegment};
+use strings::{self, SourceMap};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TokenType {
     LeftBrace,    // {
     RightBrace,   // }
@@ -11,21 +21,72 @@ pub enum TokenType {
     True,         // true
     False,        // false
     Null,         // null
+    LineComment,  // // ...
+    BlockComment, // /* ... */
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Position {
     pub line: usize,
     pub column: usize,
     pub index: usize,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn len(&self) -> usize {
+        self.end.index - self.start.index
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Whether the byte at `offset` is inside the span
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start.index <= offset && offset < self.end.index
+    }
+
+    pub fn contains_span(&self, other: Span) -> bool {
+        self.start.index <= other.start.index && other.end.index <= self.end.index
+    }
+
+    pub fn overlaps(&self, other: Span) -> bool {
+        self.start.index < other.end.index && other.start.index < self.end.index
+    }
+
+    // The smallest span covering both spans
+    pub fn cover(&self, other: Span) -> Span {
+        Span {
+            start: if other.start.index < self.start.index {
+                other.start
+            } else {
+                self.start
+            },
+            end: if other.end.index > self.end.index {
+                other.end
+            } else {
+                self.end
+            },
+        }
+    }
+
+    // The source text of the span, `src` being the parsed document
+    pub fn text<'a>(&self, src: &'a str) -> &'a str {
+        &src[self.start.index..self.end.index]
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Token {
     pub kind: TokenType,
     pub value: Option<String>,
-    pub start: Position,
-    pub end: Position,
+    pub span: Span,
 }
 
 
@@ -41,50 +102,235 @@ pub enum ArrayStatus {
     Invalid(Vec<ArrayError>),
 }
 
+#[derive(Clone, Debug)]
+pub enum StringError {
+    ControlCharacter(Position),
+    InvalidEscape(Position),
+    InvalidUnicodeEscape(Position),
+    LoneSurrogate(Position),
+}
+
+#[derive(Clone, Debug)]
+pub enum StringStatus {
+    Valid,
+    Invalid(Vec<StringError>),
+}
+
 #[derive(Clone, Debug)]
 pub enum Node {
     Object {
         children: Vec<Property>,
-        start: Position,
-        end: Position,
+        span: Span,
     },
     Array {
         status: ArrayStatus,
         children: Vec<Node>,
-        start: Position,
-        end: Position,
+        span: Span,
     },
     String {
+        status: StringStatus,
         raw: String,
-        start: Position,
-        end: Position,
+        span: Span,
     },
     Number {
         raw: String,
-        start: Position,
-        end: Position,
+        span: Span,
     },
     Boolean {
         raw: String,
-        start: Position,
-        end: Position,
+        span: Span,
     },
     Null {
         raw: String,
-        start: Position,
-        end: Position,
+        span: Span,
     },
 }
 
 impl Node {
+    pub fn span(&self) -> Span {
+        match *self {
+            Node::Object { span, .. } => span,
+            Node::Array { span, .. } => span,
+            Node::String { span, .. } => span,
+            Node::Number { span, .. } => span,
+            Node::Boolean { span, .. } => span,
+            Node::Null { span, .. } => span,
+        }
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        match *self {
+            Node::Object { .. } => NodeKind::Object,
+            Node::Array { .. } => NodeKind::Array,
+            Node::String { .. } => NodeKind::String,
+            Node::Number { .. } => NodeKind::Number,
+            Node::Boolean { .. } => NodeKind::Boolean,
+            Node::Null { .. } => NodeKind::Null,
+        }
+    }
+
+    pub fn start(&self) -> Position {
+        self.span().start
+    }
+
     pub fn end(&self) -> Position {
-        match self {
-            &Node::Object { end, .. } => end,
-            &Node::Array { end, .. } => end,
-            &Node::String { end, .. } => end,
-            &Node::Number { end, .. } => end,
-            &Node::Boolean { end, .. } => end,
-            &Node::Null { end, .. } => end,
+        self.span().end
+    }
+
+    // The unescaped contents of a String node
+    pub fn decoded(&self) -> Option<Cow<'_, str>> {
+        match *self {
+            Node::String { ref raw, .. } => Some(strings::decode(raw)),
+            _ => None,
+        }
+    }
+
+    pub fn source_map(&self) -> Option<SourceMap> {
+        match *self {
+            Node::String { ref raw, span, .. } => Some(SourceMap::new(raw, span.start)),
+            _ => None,
+        }
+    }
+
+    fn number(&self) -> Result<&str, NumberError> {
+        match *self {
+            Node::Number { ref raw, .. } => Ok(raw),
+            _ => Err(NumberError::NotANumber),
+        }
+    }
+
+    // The exact value of a Number node
+    pub fn decimal(&self) -> Result<Decimal, NumberError> {
+        self.number().and_then(number::decimal)
+    }
+
+    pub fn as_i64(&self) -> Result<i64, NumberError> {
+        self.number().and_then(number::as_i64)
+    }
+
+    pub fn as_u64(&self) -> Result<u64, NumberError> {
+        self.number().and_then(number::as_u64)
+    }
+
+    pub fn as_i128(&self) -> Result<i128, NumberError> {
+        self.number().and_then(number::as_i128)
+    }
+
+    pub fn as_f64(&self) -> Result<f64, NumberError> {
+        self.number().and_then(number::as_f64)
+    }
+
+    pub fn as_f64_rounded(&self) -> Result<f64, NumberError> {
+        self.number().and_then(number::as_f64_rounded)
+    }
+
+    pub fn as_object(&self) -> Option<&[Property]> {
+        match *self {
+            Node::Object { ref children, .. } => Some(children),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Node]> {
+        match *self {
+            Node::Array { ref children, .. } => Some(children),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Node::Boolean { ref raw, .. } => Some(raw == "true"),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(*self, Node::Null { .. })
+    }
+
+    // The value of an Object member, see `property`
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.property(key).map(|property| &property.value)
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&Node> {
+        self.as_array().and_then(|children| children.get(index))
+    }
+
+    // The keys of an Object in source order, duplicates included. Empty for
+    // anything else.
+    pub fn keys(&self) -> impl Iterator<Item = &Identifier> {
+        self.as_object()
+            .unwrap_or(&[])
+            .iter()
+            .map(|property| &property.key)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&Identifier, &Node)> {
+        self.as_object()
+            .unwrap_or(&[])
+            .iter()
+            .map(|property| (&property.key, &property.value))
+    }
+
+    // Resolves a JSON Pointer (RFC 6901) such as `/servers/0/port` against
+    // this node
+    pub fn pointer(&self, pointer: &str) -> Option<&Node> {
+        pointer::resolve(self, pointer)
+    }
+
+    // The JSON Pointer from this node to `target`, a node somewhere in this
+    // tree
+    pub fn pointer_of(&self, target: &Node) -> Option<String> {
+        pointer::path_of(self, target).map(|path| pointer::to_pointer(&path))
+    }
+
+    pub fn path_of(&self, target: &Node) -> Option<Vec<PathSegment>> {
+        pointer::path_of(self, target)
+    }
+
+    // What is at byte `offset` of `src`, the document this node was parsed
+    // from
+    pub fn node_at<'a>(&'a self, src: &str, offset: usize) -> Option<Location<'a>> {
+        location::node_at(self, src, offset)
+    }
+
+    // Runs a JSONPath query with this node as the root (`$`)
+    pub fn query(&self, path: &str) -> Result<Vec<QueryMatch<'_>>, JsonPathError> {
+        jsonpath::query(self, path)
+    }
+
+    // Looks up an Object member by its unescaped key. As with most JSON
+    // parsers, the last member wins when a key is duplicated.
+    pub fn property(&self, key: &str) -> Option<&Property> {
+        match *self {
+            Node::Object { ref children, .. } => {
+                children.iter().rev().find(|property| property.key.matches(key))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Index<&str> for Node {
+    type Output = Node;
+
+    fn index(&self, key: &str) -> &Node {
+        match self.get(key) {
+            Some(node) => node,
+            None => panic!("no member {:?} in node", key),
+        }
+    }
+}
+
+impl Index<usize> for Node {
+    type Output = Node;
+
+    fn index(&self, index: usize) -> &Node {
+        match self.get_index(index) {
+            Some(node) => node,
+            None => panic!("no element {} in node", index),
         }
     }
 }
@@ -101,20 +347,68 @@ pub struct Property {
     pub status: PropertyStatus,
     pub key: Identifier,
     pub value: Node,
-    pub start: Position,
-    pub end: Position,
+    pub span: Span,
+}
+
+impl Property {
+    pub fn span(&self) -> Span {
+        self.span
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct Identifier {
+    pub status: StringStatus,
     pub raw: String,
-    pub start: Position,
-    pub end: Position,
+    pub span: Span,
+}
+
+impl Identifier {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    // The unescaped key
+    pub fn decoded(&self) -> Cow<'_, str> {
+        strings::decode(&self.raw)
+    }
+
+    pub fn source_map(&self) -> SourceMap {
+        SourceMap::new(&self.raw, self.span.start)
+    }
+
+    pub fn matches(&self, key: &str) -> bool {
+        self.decoded() == key
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CommentKind {
+    Line,
+    Block,
+}
+
+// A comment in a JSONC document. The text includes the `//` or `/* */`.
+#[derive(Clone, Debug)]
+pub struct Comment {
+    pub kind: CommentKind,
+    pub text: String,
+    pub span: Span,
 }
 
 #[derive(Clone, Debug)]
 pub enum ParseErrorType {
     TrailingComma,
+    // Comments are only allowed by `parse_jsonc`
+    Comment,
+    // Text that doesn't start any token
+    InvalidToken,
+    // A token the grammar doesn't allow where it was found
+    UnexpectedToken,
+    // The input ended part way through a value
+    UnexpectedEnd,
+    // The input is past the 4GiB a Document can hold
+    TooLarge,
     Unknown,
 }
 
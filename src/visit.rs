@@ -0,0 +1,128 @@
+use types::{Identifier, Node, Property};
+
+// Read-only traversal of a tree. Every method defaults to visiting the
+// children of what it's given, so implementations only override the parts
+// they care about and call the matching `walk_*` function to keep
+// descending.
+pub trait Visitor {
+    fn visit_node(&mut self, node: &Node) {
+        walk_node(self, node)
+    }
+
+    fn visit_object(&mut self, node: &Node) {
+        walk_object(self, node)
+    }
+
+    fn visit_property(&mut self, property: &Property) {
+        walk_property(self, property)
+    }
+
+    fn visit_key(&mut self, _key: &Identifier) {}
+
+    fn visit_array(&mut self, node: &Node) {
+        walk_array(self, node)
+    }
+
+    fn visit_string(&mut self, _node: &Node) {}
+
+    fn visit_number(&mut self, _node: &Node) {}
+
+    fn visit_boolean(&mut self, _node: &Node) {}
+
+    fn visit_null(&mut self, _node: &Node) {}
+}
+
+pub fn walk_node<V: Visitor + ?Sized>(visitor: &mut V, node: &Node) {
+    match *node {
+        Node::Object { .. } => visitor.visit_object(node),
+        Node::Array { .. } => visitor.visit_array(node),
+        Node::String { .. } => visitor.visit_string(node),
+        Node::Number { .. } => visitor.visit_number(node),
+        Node::Boolean { .. } => visitor.visit_boolean(node),
+        Node::Null { .. } => visitor.visit_null(node),
+    }
+}
+
+pub fn walk_object<V: Visitor + ?Sized>(visitor: &mut V, node: &Node) {
+    if let Node::Object { ref children, .. } = *node {
+        for property in children {
+            visitor.visit_property(property);
+        }
+    }
+}
+
+pub fn walk_property<V: Visitor + ?Sized>(visitor: &mut V, property: &Property) {
+    visitor.visit_key(&property.key);
+    visitor.visit_node(&property.value);
+}
+
+pub fn walk_array<V: Visitor + ?Sized>(visitor: &mut V, node: &Node) {
+    if let Node::Array { ref children, .. } = *node {
+        for child in children {
+            visitor.visit_node(child);
+        }
+    }
+}
+
+// Traversal that can rewrite the tree in place, either by changing what it
+// is given or by replacing it outright (`*node = ...`). A replacement is only
+// descended into if the implementation walks it.
+pub trait VisitorMut {
+    fn visit_node_mut(&mut self, node: &mut Node) {
+        walk_node_mut(self, node)
+    }
+
+    fn visit_object_mut(&mut self, node: &mut Node) {
+        walk_object_mut(self, node)
+    }
+
+    fn visit_property_mut(&mut self, property: &mut Property) {
+        walk_property_mut(self, property)
+    }
+
+    fn visit_key_mut(&mut self, _key: &mut Identifier) {}
+
+    fn visit_array_mut(&mut self, node: &mut Node) {
+        walk_array_mut(self, node)
+    }
+
+    fn visit_string_mut(&mut self, _node: &mut Node) {}
+
+    fn visit_number_mut(&mut self, _node: &mut Node) {}
+
+    fn visit_boolean_mut(&mut self, _node: &mut Node) {}
+
+    fn visit_null_mut(&mut self, _node: &mut Node) {}
+}
+
+pub fn walk_node_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Node) {
+    match *node {
+        Node::Object { .. } => visitor.visit_object_mut(node),
+        Node::Array { .. } => visitor.visit_array_mut(node),
+        Node::String { .. } => visitor.visit_string_mut(node),
+        Node::Number { .. } => visitor.visit_number_mut(node),
+        Node::Boolean { .. } => visitor.visit_boolean_mut(node),
+        Node::Null { .. } => visitor.visit_null_mut(node),
+    }
+}
+
+pub fn walk_object_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Node) {
+    if let Node::Object { ref mut children, .. } = *node {
+        for property in children {
+            visitor.visit_property_mut(property);
+        }
+    }
+}
+
+pub fn walk_property_mut<V: VisitorMut + ?Sized>(visitor: &mut V, property: &mut Property) {
+    visitor.visit_key_mut(&mut property.key);
+    visitor.visit_node_mut(&mut property.value);
+}
+
+pub fn walk_array_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Node) {
+    if let Node::Array { ref mut children, .. } = *node {
+        for child in children {
+            visitor.visit_node_mut(child);
+        }
+    }
+}
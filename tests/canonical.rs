@@ -0,0 +1,106 @@
+extern crate json_ast;
+
+use json_ast::{canonicalize, parse, CanonicalError, Node, Parsed};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+fn canonical(input: &str) -> String {
+  canonicalize(&tree(input)).unwrap()
+}
+
+#[test]
+fn rfc_example() {
+  let input = r#"{
+    "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+    "string": "€$\u000F\u000aA'B\"\\\\\"\/",
+    "literals": [null, true, false]
+  }"#;
+  assert_eq!(
+    canonical(input),
+    r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+  );
+}
+
+#[test]
+fn keys_sort_by_utf16_code_units() {
+  let input = r#"{
+    "€": "Euro Sign",
+    "\r": "Carriage Return",
+    "דּ": "Hebrew Letter Dalet With Dagesh",
+    "1": "One",
+    "😀": "Emoji: Grinning Face",
+    "\u0080": "Control",
+    "ö": "Latin Small Letter O With Diaeresis"
+  }"#;
+  let values: Vec<String> = match tree(&canonical(input)) {
+    Node::Object { children, .. } => children
+      .iter()
+      .map(|property| property.value.decoded().unwrap().into_owned())
+      .collect(),
+    _ => panic!("expected an object"),
+  };
+  assert_eq!(
+    values,
+    vec![
+      "Carriage Return",
+      "One",
+      "Control",
+      "Latin Small Letter O With Diaeresis",
+      "Euro Sign",
+      "Emoji: Grinning Face",
+      "Hebrew Letter Dalet With Dagesh",
+    ]
+  );
+}
+
+#[test]
+fn numbers_print_like_ecmascript() {
+  for &(input, expected) in &[
+    ("0", "0"),
+    ("-0", "0"),
+    ("-0.0e5", "0"),
+    ("5e-324", "5e-324"),
+    ("-5e-324", "-5e-324"),
+    ("1.7976931348623157e308", "1.7976931348623157e+308"),
+    ("9007199254740992", "9007199254740992"),
+    ("9007199254740993", "9007199254740992"),
+    ("295147905179352830000", "295147905179352830000"),
+    ("1e20", "100000000000000000000"),
+    ("1e21", "1e+21"),
+    ("0.000001", "0.000001"),
+    ("1e-7", "1e-7"),
+    ("123e-20", "1.23e-18"),
+    ("-1.5", "-1.5"),
+    ("100", "100"),
+  ] {
+    assert_eq!(canonical(input), expected, "{}", input);
+  }
+}
+
+#[test]
+fn errors_point_at_the_offending_node() {
+  let input = r#"{"a": 1, "b": [1e400]}"#;
+  match canonicalize(&tree(input)) {
+    Err(CanonicalError::UnrepresentableNumber(span)) => assert_eq!(span.text(input), "1e400"),
+    other => panic!("unexpected {:?}", other),
+  }
+
+  let input = r#"{"a": 1, "b": [], "a": 2}"#;
+  match canonicalize(&tree(input)) {
+    Err(CanonicalError::DuplicateKey(key, span)) => {
+      assert_eq!(key, "a");
+      assert_eq!(span.text(input), r#""a": 2"#);
+    }
+    other => panic!("unexpected {:?}", other),
+  }
+
+  let input = r#"["\ud800"]"#;
+  let error = canonicalize(&tree(input)).unwrap_err();
+  assert_eq!(error, CanonicalError::InvalidString(error.span()));
+  assert_eq!(error.span().text(input), r#""\ud800""#);
+}
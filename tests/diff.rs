@@ -0,0 +1,159 @@
+extern crate json_ast;
+
+use json_ast::{diff, diff_with, parse, Change, ChangeKind, DiffOptions, Node, Parsed};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+// Each change as its kind, pointer and the text of its spans
+fn changes(
+  old: &str,
+  new: &str,
+  options: &DiffOptions,
+) -> Vec<(ChangeKind, String, Option<String>, Option<String>)> {
+  diff_with(&tree(old), &tree(new), options)
+    .into_iter()
+    .map(|change: Change| {
+      (
+        change.kind,
+        change.pointer,
+        change.old.map(|span| span.text(old).to_string()),
+        change.new.map(|span| span.text(new).to_string()),
+      )
+    })
+    .collect()
+}
+
+fn text(text: &str) -> Option<String> {
+  Some(text.to_string())
+}
+
+#[test]
+fn object_members() {
+  let old = r#"{"name": "api", "port": 80, "tls": {"on": false}, "tags": ["a"], "a/b": 1}"#;
+  let new = r#"{"name": "api", "port": 8080, "tls": {"on": true, "ca": null}, "tags": "a", "x": []}"#;
+  assert_eq!(
+    changes(old, new, &DiffOptions::default()),
+    vec![
+      (ChangeKind::Changed, "/port".to_string(), text("80"), text("8080")),
+      (ChangeKind::Changed, "/tls/on".to_string(), text("false"), text("true")),
+      (ChangeKind::Added, "/tls/ca".to_string(), None, text(r#""ca": null"#)),
+      (ChangeKind::TypeChanged, "/tags".to_string(), text(r#"["a"]"#), text(r#""a""#)),
+      (ChangeKind::Added, "/x".to_string(), None, text(r#""x": []"#)),
+      (ChangeKind::Removed, "/a~1b".to_string(), text(r#""a/b": 1"#), None),
+    ]
+  );
+  assert!(diff(&tree(old), &tree(old)).is_empty());
+}
+
+#[test]
+fn document_order() {
+  let pointers = |old: &str, new: &str| -> Vec<String> {
+    changes(old, new, &DiffOptions::default())
+      .into_iter()
+      .map(|change| change.1)
+      .collect()
+  };
+  assert_eq!(pointers(r#"{"x": 1, "z": 2}"#, r#"{"x": 3}"#), vec!["/x", "/z"]);
+  assert_eq!(
+    pointers(r#"{"w": 0, "x": 1, "y": 2}"#, r#"{"x": 3, "z": 4}"#),
+    vec!["/w", "/x", "/z", "/y"]
+  );
+}
+
+#[test]
+fn key_order() {
+  let old = r#"{"a": 1, "b": {"c": 2, "d": 3}}"#;
+  let new = r#"{"b": {"c": 2, "d": 3}, "a": 1}"#;
+  assert_eq!(
+    changes(old, new, &DiffOptions::default()),
+    vec![(ChangeKind::Reordered, "".to_string(), text(old), text(new))]
+  );
+  let options = DiffOptions {
+    ignore_key_order: true,
+    ..DiffOptions::default()
+  };
+  assert!(changes(old, new, &options).is_empty());
+}
+
+#[test]
+fn numbers_and_strings() {
+  let old = r#"{"a": 1.0, "b": 0, "c": "caf\u00e9", "d": 1e2}"#;
+  let new = r#"{"a": 1, "b": -0, "c": "café", "d": 100}"#;
+  let pointers: Vec<String> = changes(old, new, &DiffOptions::default())
+    .into_iter()
+    .map(|change| change.1)
+    .collect();
+  assert_eq!(pointers, vec!["/a", "/b", "/d"]);
+  let options = DiffOptions {
+    numeric: true,
+    ..DiffOptions::default()
+  };
+  assert!(changes(old, new, &options).is_empty());
+}
+
+#[test]
+fn array_elements() {
+  let old = r#"["a", "b", "c", "d", {"k": 1}]"#;
+  let new = r#"["a", "c", "d", "b", {"k": 2}, "e"]"#;
+  assert_eq!(
+    changes(old, new, &DiffOptions::default()),
+    vec![
+      (
+        ChangeKind::Moved {
+          from: "/1".to_string()
+        },
+        "/3".to_string(),
+        text(r#""b""#),
+        text(r#""b""#)
+      ),
+      (ChangeKind::Changed, "/4/k".to_string(), text("1"), text("2")),
+      (ChangeKind::Added, "/5".to_string(), None, text(r#""e""#)),
+    ]
+  );
+
+  let old = "[1, 2, 3, 4]";
+  let new = "[1, 5, 4]";
+  assert_eq!(
+    changes(old, new, &DiffOptions::default()),
+    vec![
+      (ChangeKind::Changed, "/1".to_string(), text("2"), text("5")),
+      (ChangeKind::Removed, "/2".to_string(), text("3"), None),
+    ]
+  );
+}
+
+#[test]
+fn long_arrays() {
+  let array = |values: &mut dyn Iterator<Item = usize>| {
+    format!("[{}]", values.map(|value| value.to_string()).collect::<Vec<_>>().join(", "))
+  };
+
+  // Only the changed elements are compared, however long the Array
+  let old = array(&mut (0..20000));
+  let new = array(&mut (0..20000).filter(|&i| i != 5 && i != 15000));
+  let found = changes(&old, &new, &DiffOptions::default());
+  let pointers: Vec<_> = found.iter().map(|change| change.1.as_str()).collect();
+  assert_eq!(pointers, vec!["/5", "/15000"]);
+  assert!(found.iter().all(|change| change.0 == ChangeKind::Removed));
+
+  // Past the edit limit nothing is matched up, so the shared half has moved
+  // and the rest is paired up by index
+  let old = array(&mut (0..3000));
+  let new = array(&mut (1500..4500));
+  let found = diff(&tree(&old), &tree(&new));
+  assert_eq!(found.len(), 3000);
+  assert_eq!(
+    found[0].kind,
+    ChangeKind::Moved {
+      from: "/1500".to_string()
+    }
+  );
+  assert_eq!(found[1500].pointer, "/1500");
+  assert_eq!(found[1500].old.unwrap().text(&old), "0");
+  assert!(found[1500..].iter().all(|change| change.kind == ChangeKind::Changed));
+}
@@ -0,0 +1,112 @@
+extern crate json_ast;
+
+use std::convert::TryFrom;
+use std::fs;
+
+use json_ast::{parse, Document, Node, NodeKind, ParseErrorType, Parsed, TreeIndex};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+const DOC: &str = r#"{
+  "name": "api",
+  "servers": [
+    { "host": "a", "port": 8080 },
+    { "host": "b", "port": 8081 }
+  ],
+  "debug": false,
+  "name": "dup"
+}"#;
+
+#[test]
+fn navigation() {
+  let doc = Document::new(&tree(DOC)).unwrap();
+  let root = doc.root();
+
+  assert_eq!(doc.kind(root), NodeKind::Object);
+  assert_eq!(doc.members(root).len(), 4);
+  assert!(doc.elements(root).is_empty());
+  assert!(doc.raw(root).is_none());
+  assert_eq!(doc.decoded(doc.get(root, "name").unwrap()).unwrap(), "dup");
+
+  let servers = doc.get(root, "servers").unwrap();
+  assert_eq!(doc.kind(servers), NodeKind::Array);
+  assert_eq!(doc.parent(servers), Some(root));
+  assert!(doc.array_status(servers).is_some());
+
+  let port = doc.get(doc.get_index(servers, 1).unwrap(), "port").unwrap();
+  assert_eq!(doc.kind(port), NodeKind::Number);
+  assert_eq!(doc.raw(port), Some("8081"));
+  assert_eq!(doc.span(port).text(DOC), "8081");
+  assert!(doc.get_index(servers, 2).is_none());
+
+  assert_eq!(doc.id_at(DOC.find("8080").unwrap() + 1).map(|id| doc.raw(id)), Some(Some("8080")));
+  assert_eq!(doc.id_at(DOC.find("servers").unwrap()), Some(root));
+  assert!(doc.id_at(DOC.len()).is_none());
+}
+
+#[test]
+fn ids_match_tree_index() {
+  let node = tree(DOC);
+  let doc = Document::new(&node).unwrap();
+  let index = TreeIndex::new(&node);
+
+  assert_eq!(doc.len(), index.len());
+  for id in doc.ids() {
+    assert_eq!(doc.span(id), index.node(id).span());
+    assert_eq!(doc.parent(id), index.parent(id));
+  }
+}
+
+#[test]
+fn round_trips_every_fixture() {
+  for kind in &["valid", "invalid"] {
+    for entry in fs::read_dir(format!("./tests/fixtures/{}/", kind)).unwrap() {
+      let path = entry.unwrap().path();
+      let input = fs::read_to_string(&path).unwrap();
+      let node = match parse(&input) {
+        Parsed::Success { tree } => tree,
+        Parsed::Failure { tree: Some(tree), .. } => tree,
+        Parsed::Failure { tree: None, .. } => continue,
+      };
+
+      let doc = Document::try_from(&node).unwrap();
+      assert_eq!(
+        format!("{:?}", doc.to_node(doc.root())),
+        format!("{:?}", node),
+        "{:?}",
+        path
+      );
+
+      let parsed = Document::parse(&input).unwrap();
+      assert_eq!(
+        format!("{:?}", parsed.to_node(parsed.root())),
+        format!("{:?}", node),
+        "{:?}",
+        path
+      );
+    }
+  }
+}
+
+#[test]
+fn parse_errors() {
+  let error = |input: &str| {
+    let error = Document::parse(input).unwrap_err();
+    (error.error_type, error.position.index)
+  };
+
+  assert!(matches!(error("{\"a\" 1}"), (ParseErrorType::UnexpectedToken, 5)));
+  assert!(matches!(error("[1, 2"), (ParseErrorType::UnexpectedEnd, 5)));
+  assert!(matches!(error("[1] 2"), (ParseErrorType::UnexpectedToken, 4)));
+  assert!(matches!(error(""), (ParseErrorType::UnexpectedEnd, 0)));
+  assert!(matches!(error("[1, @]"), (ParseErrorType::InvalidToken, 4)));
+  assert!(matches!(error("[1] @"), (ParseErrorType::InvalidToken, 4)));
+
+  let doc = Document::parse("// settings\n{ \"a\": 1 /* one */ }").unwrap();
+  assert_eq!(doc.raw(doc.get(doc.root(), "a").unwrap()), Some("1"));
+}
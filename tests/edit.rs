@@ -0,0 +1,193 @@
+extern crate json_ast;
+
+use json_ast::edit;
+use json_ast::{apply_edits, parse, parse_jsonc, EditError, Node, Parsed, TextEdit};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+fn jsonc(input: &str) -> Node {
+  match parse_jsonc(input).0 {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+fn set(src: &str, pointer: &str, value: &str) -> String {
+  let edits = edit::set(src, &jsonc(src), pointer, &tree(value)).unwrap();
+  apply_edits(src, &edits).unwrap()
+}
+
+fn insert(src: &str, pointer: &str, value: &str) -> String {
+  let edits = edit::insert(src, &jsonc(src), pointer, &tree(value)).unwrap();
+  apply_edits(src, &edits).unwrap()
+}
+
+fn remove(src: &str, pointer: &str) -> String {
+  let edits = edit::remove(src, &jsonc(src), pointer).unwrap();
+  apply_edits(src, &edits).unwrap()
+}
+
+const SETTINGS: &str = r#"{
+    // Editor
+    "editor.fontSize": 14, // points
+    "editor.rulers": [80, 120],
+
+    /* Files */
+    "files.exclude": {
+        "**/.git": true
+    }
+}
+"#;
+
+#[test]
+fn set_replaces_values_in_place() {
+  assert_eq!(
+    set(SETTINGS, "/editor.fontSize", "16"),
+    SETTINGS.replace("14", "16")
+  );
+  assert_eq!(
+    set(SETTINGS, "/editor.rulers/1", "100"),
+    SETTINGS.replace("120", "100")
+  );
+  assert_eq!(
+    set(SETTINGS, "/editor.fontSize", r#"{"a": [1, 2]}"#),
+    SETTINGS.replace(
+      "14,",
+      r#"{
+        "a": [1, 2]
+    },"#
+    )
+  );
+
+  let src = "[1, 2]";
+  assert_eq!(edit::set(src, &tree(src), "/1", &tree("2")), Ok(vec![]));
+  assert_eq!(set(src, "", "null"), "null");
+}
+
+#[test]
+fn set_adds_missing_members() {
+  assert_eq!(
+    set(SETTINGS, "/files.exclude/build", "false"),
+    SETTINGS.replace(
+      r#""**/.git": true"#,
+      r#""**/.git": true,
+        "build": false"#
+    )
+  );
+  assert_eq!(
+    set(SETTINGS, "/window", r#"{"zoom": 1, "title": []}"#),
+    SETTINGS.replace(
+      "    }\n}",
+      r#"    },
+    "window": {
+        "zoom": 1,
+        "title": []
+    }
+}"#
+    )
+  );
+  assert_eq!(
+    set(SETTINGS, "/editor.rulers/-", "140"),
+    SETTINGS.replace("[80, 120]", "[80, 120, 140]")
+  );
+  assert_eq!(set("{}", "/a", "1"), "{\n  \"a\": 1\n}");
+  assert_eq!(set("[ ]", "/0", "1"), "[1]");
+  assert_eq!(set("{\"a\": 1}", "/\"q\"", "2"), "{\"a\": 1, \"\\\"q\\\"\": 2}");
+  // The comment after the last member stays with it
+  assert_eq!(
+    set("{\r\n\t\"a\": 1 // one\r\n}", "/b", "[true]"),
+    "{\r\n\t\"a\": 1, // one\r\n\t\"b\": [true]\r\n}"
+  );
+}
+
+#[test]
+fn insert_goes_before_an_index() {
+  assert_eq!(
+    insert(SETTINGS, "/editor.rulers/0", "40"),
+    SETTINGS.replace("[80, 120]", "[40, 80, 120]")
+  );
+  assert_eq!(
+    insert(SETTINGS, "/editor.rulers/2", "140"),
+    SETTINGS.replace("[80, 120]", "[80, 120, 140]")
+  );
+
+  let src = "[\n  1,\n  // two\n  2\n]";
+  assert_eq!(
+    insert(src, "/1", r#"{"k": 3}"#),
+    "[\n  1,\n  {\n    \"k\": 3\n  },\n  // two\n  2\n]"
+  );
+}
+
+#[test]
+fn remove_takes_commas_and_comments() {
+  assert_eq!(
+    remove(SETTINGS, "/editor.fontSize"),
+    SETTINGS.replace("    // Editor\n    \"editor.fontSize\": 14, // points\n", "")
+  );
+  assert_eq!(
+    remove(SETTINGS, "/files.exclude"),
+    r#"{
+    // Editor
+    "editor.fontSize": 14, // points
+    "editor.rulers": [80, 120]
+}
+"#
+  );
+  assert_eq!(
+    remove(SETTINGS, "/editor.rulers/0"),
+    SETTINGS.replace("[80, 120]", "[120]")
+  );
+  assert_eq!(
+    remove(SETTINGS, "/editor.rulers/1"),
+    SETTINGS.replace("[80, 120]", "[80]")
+  );
+  assert_eq!(
+    remove(SETTINGS, "/files.exclude/**~1.git"),
+    SETTINGS.replace("{\n        \"**/.git\": true\n    }", "{}")
+  );
+
+  assert_eq!(remove("[\n\t1, // one\n\t2 // two\n]", "/1"), "[\n\t1 // one\n]");
+  // Comments that don't belong to the member are kept
+  assert_eq!(remove("{ /* keep */ \"a\": 1 }", "/a"), "{ /* keep */ }");
+  assert_eq!(remove("{\n  \"a\": 1\n  // keep\n}", "/a"), "{\n  // keep\n}");
+}
+
+#[test]
+fn errors() {
+  let src = r#"{"a": [1], "b": "x"}"#;
+  let doc = tree(src);
+  let value = tree("1");
+  assert_eq!(edit::set(src, &doc, "a", &value), Err(EditError::InvalidPointer));
+  assert_eq!(edit::set(src, &doc, "/c/d", &value), Err(EditError::NotFound));
+  assert_eq!(edit::set(src, &doc, "/b/0", &value), Err(EditError::NotFound));
+  assert_eq!(edit::set(src, &doc, "/a/2", &value), Err(EditError::NotFound));
+  assert_eq!(edit::insert(src, &doc, "/a/01", &value), Err(EditError::NotFound));
+  assert_eq!(edit::remove(src, &doc, "/a/1"), Err(EditError::NotFound));
+  assert_eq!(edit::remove(src, &doc, "/c"), Err(EditError::NotFound));
+  assert_eq!(edit::remove(src, &doc, ""), Err(EditError::RemoveRoot));
+}
+
+#[test]
+fn text_that_isnt_the_tree() {
+  let doc = tree(r#"{"a": [1], "b": "x"}"#);
+  let value = tree("1");
+  assert_eq!(edit::set("{}", &doc, "/a", &value), Err(EditError::SourceMismatch));
+  assert_eq!(edit::set(r#"{"a": [2], "b": "x"}"#, &doc, "/c", &value), Err(EditError::SourceMismatch));
+  assert_eq!(edit::remove(r#"{"a": [1], "b": "é"}"#, &doc, "/a"), Err(EditError::SourceMismatch));
+}
+
+#[test]
+fn apply_edits_checks_its_edits() {
+  let edit = |start: usize, end: usize| TextEdit::new(start, end, "x");
+  assert_eq!(apply_edits("abc", &[edit(2, 3), edit(0, 1)]), Ok("xbx".to_string()));
+  assert_eq!(apply_edits("abc", &[edit(1, 1), edit(1, 1)]), Ok("axxbc".to_string()));
+  assert_eq!(apply_edits("abc", &[edit(0, 2), edit(1, 3)]), Err(EditError::Overlapping));
+  assert_eq!(apply_edits("abc", &[edit(2, 4)]), Err(EditError::OutOfRange));
+  assert_eq!(apply_edits("abc", &[edit(2, 1)]), Err(EditError::OutOfRange));
+  assert_eq!(apply_edits("é", &[edit(1, 2)]), Err(EditError::OutOfRange));
+}
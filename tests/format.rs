@@ -0,0 +1,240 @@
+extern crate json_ast;
+
+use std::fs;
+
+use json_ast::{apply_edits, format, format_range, parse, parse_jsonc, FormatOptions, Indent, KeySpacing,
+               LineEnding, Node, Parsed};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+fn format_str(input: &str, options: &FormatOptions) -> String {
+  format(&tree(input), options)
+}
+
+#[test]
+fn default_layout() {
+  let input = r#"{"name":"api","ports":[80,443],"tls":{"enabled":true,"ca":null},"tags":[],"meta":{}}"#;
+  assert_eq!(
+    format_str(input, &FormatOptions::default()),
+    r#"{
+  "name": "api",
+  "ports": [80, 443],
+  "tls": {
+    "enabled": true,
+    "ca": null
+  },
+  "tags": [],
+  "meta": {}
+}
+"#
+  );
+}
+
+#[test]
+fn lexemes_are_kept() {
+  let input = r#"[1.50, -0, 1E+3, "caf\u00e9", "tab\t", "\/"]"#;
+  assert_eq!(
+    format_str(input, &FormatOptions::default()),
+    "[1.50, -0, 1E+3, \"caf\\u00e9\", \"tab\\t\", \"\\/\"]\n"
+  );
+}
+
+#[test]
+fn indentation_and_line_endings() {
+  let options = FormatOptions {
+    indent: Indent::Tabs,
+    line_ending: LineEnding::CrLf,
+    final_newline: false,
+    ..FormatOptions::default()
+  };
+  assert_eq!(
+    format_str(r#"{"a":{"b":1}}"#, &options),
+    "{\r\n\t\"a\": {\r\n\t\t\"b\": 1\r\n\t}\r\n}"
+  );
+
+  let options = FormatOptions {
+    indent: Indent::Spaces(4),
+    ..FormatOptions::default()
+  };
+  assert_eq!(format_str(r#"{"a":1}"#, &options), "{\n    \"a\": 1\n}\n");
+}
+
+#[test]
+fn key_spacing() {
+  let input = r#"{"a":1,"long":2}"#;
+  let with = |key_spacing| FormatOptions {
+    key_spacing,
+    ..FormatOptions::default()
+  };
+
+  assert_eq!(format_str(input, &with(KeySpacing::Tight)), "{\n  \"a\":1,\n  \"long\":2\n}\n");
+  assert_eq!(format_str(input, &with(KeySpacing::Space)), "{\n  \"a\": 1,\n  \"long\": 2\n}\n");
+  assert_eq!(format_str(input, &with(KeySpacing::Aligned)), "{\n  \"a\":    1,\n  \"long\": 2\n}\n");
+}
+
+#[test]
+fn long_arrays_wrap() {
+  let input = r#"{"values":[100,200,300,400,500,600],"nested":[[1,2],{"a":1}]}"#;
+  let options = FormatOptions {
+    max_width: 24,
+    ..FormatOptions::default()
+  };
+  assert_eq!(
+    format_str(input, &options),
+    r#"{
+  "values": [
+    100,
+    200,
+    300,
+    400,
+    500,
+    600
+  ],
+  "nested": [
+    [1, 2],
+    {
+      "a": 1
+    }
+  ]
+}
+"#
+  );
+
+  let options = FormatOptions {
+    compact_arrays: true,
+    ..options
+  };
+  assert_eq!(
+    format_str(input, &options),
+    r#"{
+  "values": [
+    100, 200, 300, 400,
+    500, 600
+  ],
+  "nested": [
+    [1, 2],
+    {
+      "a": 1
+    }
+  ]
+}
+"#
+  );
+}
+
+#[test]
+fn formatting_is_idempotent() {
+  let options = vec![
+    FormatOptions::default(),
+    FormatOptions {
+      indent: Indent::Tabs,
+      line_ending: LineEnding::CrLf,
+      key_spacing: KeySpacing::Aligned,
+      ..FormatOptions::default()
+    },
+    FormatOptions {
+      max_width: 30,
+      compact_arrays: true,
+      key_spacing: KeySpacing::Tight,
+      final_newline: false,
+      ..FormatOptions::default()
+    },
+  ];
+
+  for entry in fs::read_dir("./tests/fixtures/valid/").unwrap() {
+    let path = entry.unwrap().path();
+    let input = fs::read_to_string(&path).unwrap();
+    let node = match parse(&input) {
+      Parsed::Success { tree } => tree,
+      _ => continue,
+    };
+
+    for options in &options {
+      let once = format(&node, options);
+      let twice = format(&tree(&once), options);
+      assert_eq!(once, twice, "{:?}", path);
+    }
+  }
+}
+
+fn format_selection(src: &str, selection: &str) -> String {
+  let start = src.find(selection).unwrap();
+  let (tree, comments) = match parse_jsonc(src) {
+    (Parsed::Success { tree }, comments) => (tree, comments),
+    _ => panic!("expected a tree"),
+  };
+  let edits = format_range(src, &tree, &comments, start, start + selection.len(), &FormatOptions::default());
+  apply_edits(src, &edits).unwrap()
+}
+
+#[test]
+fn range_formatting() {
+  let src = r#"{
+    "keep"  :  [1,2],
+    "a" :{"b":  [1,2,
+      3],"c":true},
+    "other":   null
+}"#;
+
+  // A single member, indented from the line it's on
+  assert_eq!(
+    format_selection(src, r#""b""#),
+    r#"{
+    "keep"  :  [1,2],
+    "a" :{"b": [1, 2, 3],"c":true},
+    "other":   null
+}"#
+  );
+
+  // A whole value
+  assert_eq!(
+    format_selection(src, r#"{"b""#),
+    r#"{
+    "keep"  :  [1,2],
+    "a" :{
+      "b": [1, 2, 3],
+      "c": true
+    },
+    "other":   null
+}"#
+  );
+
+  // A run of members
+  assert_eq!(
+    format_selection(src, "2],\n    \"a\""),
+    r#"{
+    "keep": [1, 2],
+    "a": {
+      "b": [1, 2, 3],
+      "c": true
+    },
+    "other":   null
+}"#
+  );
+
+  // The brackets of a container take all of it
+  let options = FormatOptions {
+    final_newline: false,
+    ..FormatOptions::default()
+  };
+  assert_eq!(format_selection(src, "\n}"), format(&tree(src), &options));
+}
+
+#[test]
+fn range_formatting_keeps_comments_and_formatted_text() {
+  let src = "[\n  1, // one\n  {\"a\":1} /* two */\n]";
+  assert_eq!(
+    format_selection(src, "1, // one\n  {"),
+    "[\n  1, // one\n  {\n    \"a\": 1\n  } /* two */\n]"
+  );
+
+  let formatted = format(&tree(r#"{"a": [1, 2], "b": {"c": null}}"#), &FormatOptions::default());
+  let start = formatted.find("\"c\"").unwrap();
+  let edits = format_range(&formatted, &tree(&formatted), &[], start, start, &FormatOptions::default());
+  assert!(edits.is_empty());
+}
@@ -1,12 +1,13 @@
+#![allow(clippy::assertions_on_constants)]
+
 extern crate json_ast;
 
 use std::fs::{self, DirEntry};
 use std::io::prelude::*;
 use std::fs::File;
 use std::path::Path;
-use json_ast::parse;
+use json_ast::{parse, Node, Parsed, StringError, StringStatus};
 use std::env;
-use std::ffi::OsString;
 
 #[test]
 fn trailing_comma_1() {
@@ -165,6 +166,136 @@ fn array_missing_and_trailing_comma() {
   assert!(true);
 }
 
+#[test]
+fn string_escapes_are_validated() {
+  let result = parse(r##"["ok \u00e9 \ud83d\ude00", "bad \q", "\udc00"]"##);
+  let children = match result {
+    Parsed::Success { tree: Node::Array { children, .. } } => children,
+    _ => panic!("expected an array"),
+  };
+
+  match children[0] {
+    Node::String { status: StringStatus::Valid, .. } => {}
+    _ => panic!("expected a valid string"),
+  }
+  match children[1] {
+    Node::String { status: StringStatus::Invalid(ref errors), .. } => match errors[..] {
+      [StringError::InvalidEscape(position)] => assert_eq!(position.index, 32),
+      _ => panic!("expected an invalid escape"),
+    },
+    _ => panic!("expected an invalid string"),
+  }
+  match children[2] {
+    Node::String { status: StringStatus::Invalid(ref errors), .. } => match errors[..] {
+      [StringError::LoneSurrogate(position)] => assert_eq!(position.index, 38),
+      _ => panic!("expected a lone surrogate"),
+    },
+    _ => panic!("expected an invalid string"),
+  }
+}
+
+#[test]
+fn raw_control_characters_in_keys() {
+  let result = parse("{\"a\tb\": 1}");
+  match result {
+    Parsed::Success { tree: Node::Object { children, .. } } => match children[0].key.status {
+      StringStatus::Invalid(ref errors) => match errors[..] {
+        [StringError::ControlCharacter(position)] => assert_eq!(position.column, 4),
+        _ => panic!("expected a control character"),
+      },
+      StringStatus::Valid => panic!("expected an invalid key"),
+    },
+    _ => panic!("expected an object"),
+  }
+}
+
+#[test]
+fn decoded_string_values() {
+  let result = parse(r##"["plain", "caf\u00e9 \"quoted\" \ud83d\ude00", "lone \ud800!"]"##);
+  let children = match result {
+    Parsed::Success { tree: Node::Array { children, .. } } => children,
+    _ => panic!("expected an array"),
+  };
+
+  assert_eq!(children[0].decoded().unwrap(), "plain");
+  assert_eq!(children[1].decoded().unwrap(), "café \"quoted\" 😀");
+  assert_eq!(children[2].decoded().unwrap(), "lone \u{FFFD}!");
+}
+
+#[test]
+fn property_lookup_uses_decoded_keys() {
+  let tree = match parse(r##"{"\u0061": 1, "b\/c": 2, "a": 3}"##) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  };
+
+  assert!(tree.property("b/c").is_some());
+  assert!(tree.property("b\\/c").is_none());
+  match tree.property("a").unwrap().value {
+    Node::Number { ref raw, .. } => assert_eq!(raw, "3"),
+    _ => panic!("expected a number"),
+  }
+  match tree {
+    Node::Object { ref children, .. } => {
+      assert!(children[0].key.matches("a"));
+      assert_eq!(children[1].key.decoded(), "b/c");
+    }
+    _ => panic!("expected an object"),
+  }
+}
+
+#[test]
+fn string_source_map() {
+  let tree = match parse("{\n  \"k\": \"a\\u00e9\\n\\ud83d\\ude00z\"\n}") {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  };
+  let value = &tree.property("k").unwrap().value;
+  assert_eq!(value.decoded().unwrap(), "aé\n😀z");
+
+  let map = value.source_map().unwrap();
+  // decoded: a(0) é(1..3) \n(3) 😀(4..8) z(8), source starts after `"` at column 9
+  let columns: Vec<usize> = (0..6).map(|i| map.position_of_char(i).unwrap().column).collect();
+  assert_eq!(columns, vec![9, 10, 16, 18, 30, 31]);
+  assert_eq!(map.position_of_byte(2).unwrap().column, 10);
+  assert_eq!(map.position_of_byte(8).unwrap().index, 31);
+  assert!(map.position_of_byte(10).is_none());
+
+  let span = map.source_range(4, 8).unwrap();
+  assert_eq!((span.start.line, span.start.column, span.end.column), (2, 18, 30));
+
+  let key = &tree.property("k").unwrap().key;
+  assert_eq!(key.source_map().position_of_char(0).unwrap().column, 4);
+}
+
+#[test]
+fn node_spans() {
+  let src = "{\n  \"a\": [1, true],\n  \"b\": null\n}";
+  let tree = match parse(src) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  };
+
+  assert_eq!(tree.span().text(src), src);
+  assert_eq!(tree.start().index, 0);
+
+  let a = tree.property("a").unwrap();
+  let b = tree.property("b").unwrap();
+  assert_eq!(a.span().text(src), "\"a\": [1, true]");
+  assert_eq!(a.key.span().text(src), "\"a\"");
+  assert_eq!(a.value.span().text(src), "[1, true]");
+  assert_eq!(b.value.span().start.line, 3);
+
+  assert!(tree.span().contains_span(a.span()));
+  assert!(a.span().contains_span(a.value.span()));
+  assert!(!a.span().overlaps(b.span()));
+  assert!(a.span().cover(b.span()).overlaps(b.value.span()));
+  assert!(a.value.span().contains(9));
+  assert!(!a.value.span().contains(8));
+  assert!(!a.value.span().contains(a.value.end().index));
+  assert_eq!(a.value.span().len(), 9);
+}
+
 // #[test]
 // fn fail_0() {
 //   parse(r##""##);
@@ -316,39 +447,37 @@ fn pass_3() {
 #[test]
 fn snapshots() {
   match env::var_os("BUILD_SNAPSHOTS") {
-    Some(val) => {
-      if val == OsString::from("true") {
-        build_snapshots();
-        assert!(true);
-      } else {
-        test_snapshots();
-      }
+    Some(ref val) if val == "true" => {
+      build_snapshots("valid");
+      build_snapshots("invalid");
     }
     _ => {
-      test_snapshots();
+      test_snapshots("valid");
+      test_snapshots("invalid");
     }
   }
 }
 
-fn build_snapshots() {
-  for entry in fs::read_dir(Path::new("./tests/fixtures/valid/")).unwrap() {
+fn build_snapshots(kind: &str) {
+  for entry in fs::read_dir(Path::new(&format!("./tests/fixtures/{}/", kind))).unwrap() {
     let entry = entry.unwrap();
-    create_snapshot(entry);
+    create_snapshot(kind, entry);
   }
 }
 
-fn test_snapshots() {
-  for entry in fs::read_dir(Path::new("./tests/fixtures/valid/")).unwrap() {
+fn test_snapshots(kind: &str) {
+  for entry in fs::read_dir(Path::new(&format!("./tests/fixtures/{}/", kind))).unwrap() {
     let entry = entry.unwrap();
-    test_snapshot(entry);
+    test_snapshot(kind, entry);
   }
   assert!(true);
 }
 
-fn test_snapshot(entry: DirEntry) {
+fn test_snapshot(kind: &str, entry: DirEntry) {
   let name = entry.file_name();
   let saved = format!(
-    "./tests/snapshots/valid/{}.snapshot",
+    "./tests/snapshots/{}/{}.snapshot",
+    kind,
     name.into_string().unwrap()
   );
   let snapshot = format_snapshot(entry);
@@ -371,11 +500,12 @@ fn format_snapshot(entry: DirEntry) -> String {
   format!("JSON:\n{}\n\nValue:\n{:#?}\n", buffer, val)
 }
 
-fn create_snapshot(entry: DirEntry) {
+fn create_snapshot(kind: &str, entry: DirEntry) {
   let name = entry.file_name();
   let snapshot = format_snapshot(entry);
   let out = format!(
-    "./tests/snapshots/valid/{}.snapshot",
+    "./tests/snapshots/{}/{}.snapshot",
+    kind,
     name.into_string().unwrap()
   );
   let mut f = File::create(out).unwrap();
@@ -0,0 +1,133 @@
+extern crate json_ast;
+
+use std::fs;
+
+use json_ast::{format, format_with_comments, parse, parse_jsonc, Comment, CommentKind, FormatOptions,
+               LineEnding, Node, ParseErrorType, Parsed};
+
+fn jsonc(input: &str) -> (Node, Vec<Comment>) {
+  match parse_jsonc(input) {
+    (Parsed::Success { tree }, comments) => (tree, comments),
+    (Parsed::Failure { tree: Some(tree), .. }, comments) => (tree, comments),
+    _ => panic!("expected a tree"),
+  }
+}
+
+fn texts(comments: &[Comment]) -> Vec<&str> {
+  comments.iter().map(|comment| comment.text.as_str()).collect()
+}
+
+#[test]
+fn comments_are_tokenized() {
+  let input = "// line\n{\"a\": /* block\n */ 1}";
+  let (tree, comments) = jsonc(input);
+
+  assert_eq!(texts(&comments), vec!["// line", "/* block\n */"]);
+  assert_eq!(comments[0].kind, CommentKind::Line);
+  assert_eq!(comments[1].kind, CommentKind::Block);
+  assert_eq!(comments[1].span.start.line, 2);
+  assert_eq!(comments[1].span.end.line, 3);
+  assert_eq!(tree.get("a").unwrap().span().start.line, 3);
+}
+
+#[test]
+fn plain_json_rejects_comments() {
+  match parse("[1, // one\n 2]") {
+    Parsed::Failure {
+      tree: Some(tree),
+      errors,
+      ..
+    } => {
+      assert_eq!(tree.as_array().unwrap().len(), 2);
+      assert_eq!(errors.len(), 1);
+      assert_eq!(errors[0].position.index, 4);
+      match errors[0].error_type {
+        ParseErrorType::Comment => {}
+        _ => panic!("expected a comment error"),
+      }
+    }
+    _ => panic!("expected a failure"),
+  }
+}
+
+#[test]
+fn comments_keep_their_places() {
+  let input = fs::read_to_string("./tests/fixtures/jsonc/settings.jsonc").unwrap();
+  let (tree, comments) = jsonc(&input);
+
+  assert_eq!(
+    format_with_comments(&tree, &comments, &FormatOptions::default()),
+    r#"{
+  "editor.fontSize": /* px */ 14,
+  "editor.rulers": [
+    80,
+    /* soft */ 100,
+    120 /* hard */
+  ],
+  "files.exclude": /* hidden */ {
+    "**/.git": true,
+    /* vcs */ "**/node_modules": true
+  },
+  "window.zoomLevel": // comment between colon and value
+    1,
+  "empty": { /* nothing here */
+  },
+  "list": [
+    1, // one
+    2, // two
+    3 // three
+  ]
+}
+"#
+  );
+
+  let input = fs::read_to_string("./tests/fixtures/jsonc/minified.jsonc").unwrap();
+  let (tree, comments) = jsonc(&input);
+  assert_eq!(
+    format_with_comments(&tree, &comments, &FormatOptions::default()),
+    r#"/*a*/ [ /*b*/
+  1,
+  /*c*/ /*d*/ { /*e*/
+    "k": /*f*/ /*g*/ [ /*h*/
+    ] /*i*/
+  } /*j*/
+] /*k*/
+"#
+  );
+}
+
+#[test]
+fn comments_follow_the_line_ending() {
+  let input = fs::read_to_string("./tests/fixtures/jsonc/block.jsonc").unwrap();
+  let (tree, comments) = jsonc(&input);
+  let options = FormatOptions {
+    line_ending: LineEnding::CrLf,
+    ..FormatOptions::default()
+  };
+  let crlf = format_with_comments(&tree, &comments, &options);
+  assert!(crlf.contains("/**\r\n   * A multi-line\r\n"));
+  assert_eq!(crlf.matches('\n').count(), crlf.matches("\r\n").count());
+
+  let (tree, comments) = jsonc(&crlf);
+  let lf = format_with_comments(&tree, &comments, &FormatOptions::default());
+  assert!(!lf.contains('\r'));
+  assert_eq!(lf, format_with_comments(&jsonc(&input).0, &jsonc(&input).1, &FormatOptions::default()));
+}
+
+#[test]
+fn no_comment_is_lost() {
+  for entry in fs::read_dir("./tests/fixtures/jsonc/").unwrap() {
+    let path = entry.unwrap().path();
+    let input = fs::read_to_string(&path).unwrap();
+    let (tree, comments) = jsonc(&input);
+
+    let options = FormatOptions::default();
+    let once = format_with_comments(&tree, &comments, &options);
+    let (reparsed, reparsed_comments) = jsonc(&once);
+    assert_eq!(texts(&comments), texts(&reparsed_comments), "{:?}", path);
+    assert_eq!(format(&tree, &options), format(&reparsed, &options), "{:?}", path);
+
+    let twice = format_with_comments(&reparsed, &reparsed_comments, &options);
+    assert_eq!(once, twice, "{:?}", path);
+  }
+}
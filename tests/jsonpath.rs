@@ -0,0 +1,218 @@
+extern crate json_ast;
+
+use json_ast::{parse, Node, Parsed};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+const STORE: &str = r#"{ "store": {
+    "book": [
+      { "category": "reference",
+        "author": "Nigel Rees",
+        "title": "Sayings of the Century",
+        "price": 8.95
+      },
+      { "category": "fiction",
+        "author": "Evelyn Waugh",
+        "title": "Sword of Honour",
+        "price": 12.99
+      },
+      { "category": "fiction",
+        "author": "Herman Melville",
+        "title": "Moby Dick",
+        "isbn": "0-553-21311-3",
+        "price": 8.99
+      },
+      { "category": "fiction",
+        "author": "J. R. R. Tolkien",
+        "title": "The Lord of the Rings",
+        "isbn": "0-395-19395-8",
+        "price": 22.99
+      }
+    ],
+    "bicycle": {
+      "color": "red",
+      "price": 399
+    }
+  }
+}"#;
+
+fn paths(doc: &Node, query: &str) -> Vec<String> {
+  doc
+    .query(query)
+    .unwrap()
+    .iter()
+    .map(|found| found.normalized_path())
+    .collect()
+}
+
+#[test]
+fn child_and_descendant_segments() {
+  let doc = tree(STORE);
+
+  assert_eq!(
+    paths(&doc, "$.store.book[*].author"),
+    vec![
+      "$['store']['book'][0]['author']",
+      "$['store']['book'][1]['author']",
+      "$['store']['book'][2]['author']",
+      "$['store']['book'][3]['author']",
+    ]
+  );
+  assert_eq!(paths(&doc, "$..author").len(), 4);
+  assert_eq!(paths(&doc, "$.store.*").len(), 2);
+  assert_eq!(paths(&doc, "$.store..price").len(), 5);
+  assert_eq!(paths(&doc, "$..book[2]"), vec!["$['store']['book'][2]"]);
+  assert_eq!(paths(&doc, "$..book[-1]"), vec!["$['store']['book'][3]"]);
+  assert_eq!(paths(&doc, "$..*").len(), 27);
+  assert_eq!(paths(&doc, "$"), vec!["$"]);
+  assert!(paths(&doc, "$.missing").is_empty());
+}
+
+#[test]
+fn matches_carry_nodes_and_spans() {
+  let doc = tree(STORE);
+
+  let found = doc.query("$.store.bicycle.color").unwrap();
+  assert_eq!(found.len(), 1);
+  assert_eq!(found[0].node.decoded().unwrap(), "red");
+  assert_eq!(found[0].span().text(STORE), "\"red\"");
+  assert_eq!(doc.pointer_of(found[0].node).unwrap(), "/store/bicycle/color");
+}
+
+#[test]
+fn slices() {
+  let doc = tree("[0, 1, 2, 3, 4, 5, 6]");
+
+  let values = |query: &str| -> Vec<i64> {
+    doc
+      .query(query)
+      .unwrap()
+      .iter()
+      .map(|found| found.node.as_i64().unwrap())
+      .collect()
+  };
+
+  assert_eq!(values("$[1:3]"), vec![1, 2]);
+  assert_eq!(values("$[5:]"), vec![5, 6]);
+  assert_eq!(values("$[:2]"), vec![0, 1]);
+  assert_eq!(values("$[1:5:2]"), vec![1, 3]);
+  assert_eq!(values("$[5:1:-2]"), vec![5, 3]);
+  assert_eq!(values("$[::-1]"), vec![6, 5, 4, 3, 2, 1, 0]);
+  assert_eq!(values("$[-2:]"), vec![5, 6]);
+  assert_eq!(values("$[::0]"), Vec::<i64>::new());
+  assert_eq!(values("$[0, 3, 0]"), vec![0, 3, 0]);
+}
+
+#[test]
+fn filters() {
+  let doc = tree(STORE);
+
+  let titles = |query: &str| -> Vec<String> {
+    doc
+      .query(query)
+      .unwrap()
+      .iter()
+      .map(|found| found.node.get("title").unwrap().decoded().unwrap().into_owned())
+      .collect()
+  };
+
+  assert_eq!(
+    titles("$..book[?@.isbn]"),
+    vec!["Moby Dick", "The Lord of the Rings"]
+  );
+  assert_eq!(
+    titles("$..book[?@.price < 10]"),
+    vec!["Sayings of the Century", "Moby Dick"]
+  );
+  assert_eq!(
+    titles("$..book[?@.price > 10 && @.category == 'fiction']"),
+    vec!["Sword of Honour", "The Lord of the Rings"]
+  );
+  assert_eq!(
+    titles("$..book[?!@.isbn || @.price > 20]"),
+    vec!["Sayings of the Century", "Sword of Honour", "The Lord of the Rings"]
+  );
+  assert_eq!(
+    titles("$..book[?@.price > $.store.bicycle.price]"),
+    Vec::<String>::new()
+  );
+  assert_eq!(titles("$..book[?match(@.author, 'Herman.*')]"), vec!["Moby Dick"]);
+  assert_eq!(
+    titles("$..book[?search(@.title, '[Oo]f')]"),
+    vec!["Sayings of the Century", "Sword of Honour", "The Lord of the Rings"]
+  );
+  assert_eq!(titles("$..book[?length(@.title) == 9]"), vec!["Moby Dick"]);
+  assert_eq!(titles("$.store[?count(@.*) == 5]"), Vec::<String>::new());
+  assert_eq!(paths(&doc, "$.store[?count(@.*) == 2]"), vec!["$['store']['bicycle']"]);
+}
+
+#[test]
+fn regex_matching_is_linear() {
+  let long = format!("[\"{}\"]", "a".repeat(200_000));
+  let doc = tree(&long);
+  assert_eq!(paths(&doc, "$[?match(@, 'a*')]"), vec!["$[0]"]);
+  assert_eq!(paths(&doc, "$[?search(@, 'b')]"), Vec::<String>::new());
+
+  let doc = tree(&format!("[\"{}b\"]", "a".repeat(30)));
+  assert_eq!(paths(&doc, "$[?match(@, '(a|a)*')]"), Vec::<String>::new());
+  assert_eq!(paths(&doc, "$[?match(@, '(a|a)*b')]"), vec!["$[0]"]);
+  assert_eq!(paths(&doc, "$[?match(@, '(a*)*c')]"), Vec::<String>::new());
+  assert_eq!(paths(&doc, "$[?search(@, 'a{2,3}b')]"), vec!["$[0]"]);
+  assert_eq!(paths(&doc, "$[?match(@, 'a{2,3}b')]"), Vec::<String>::new());
+  assert_eq!(paths(&doc, "$[?match(@, '((a{1000}){1000}){1000}')]"), Vec::<String>::new());
+}
+
+#[test]
+fn comparisons_follow_the_rfc() {
+  let doc = tree(r#"[{"a": 1}, {"a": "1"}, {"a": [1]}, {"a": null}, {}, {"a": {"b": 2}}]"#);
+
+  assert_eq!(paths(&doc, "$[?@.a == 1]"), vec!["$[0]"]);
+  assert_eq!(paths(&doc, "$[?@.a == '1']"), vec!["$[1]"]);
+  assert_eq!(paths(&doc, "$[?@.a == null]"), vec!["$[3]"]);
+  assert_eq!(paths(&doc, "$[?@.a == @.missing]"), vec!["$[4]"]);
+  assert_eq!(paths(&doc, "$[?@.a == $[5].a]"), vec!["$[5]"]);
+  assert_eq!(paths(&doc, "$[?@.a == $[2].a]"), vec!["$[2]"]);
+  assert_eq!(paths(&doc, "$[?@.a <= 1]"), vec!["$[0]"]);
+  assert_eq!(paths(&doc, "$[?@.a != 1]").len(), 5);
+}
+
+#[test]
+fn keys_are_compared_decoded() {
+  let doc = tree(r#"{"ab": 1, "it's": 2, "tab\t": 3}"#);
+
+  assert_eq!(paths(&doc, "$.ab"), vec!["$['ab']"]);
+  assert_eq!(paths(&doc, "$[\"it's\"]"), vec!["$['it\\'s']"]);
+  assert_eq!(paths(&doc, "$['tab\\t']"), vec!["$['tab\\t']"]);
+}
+
+#[test]
+fn invalid_queries() {
+  let doc = tree(STORE);
+
+  for query in &[
+    "",
+    "store",
+    "$.",
+    "$[",
+    "$[01]",
+    "$[-0]",
+    "$['a'",
+    "$[?@.a == @..b]",
+    "$[?length(@.a)]",
+    "$[?match(@.a)]",
+    "$[?unknown(@)]",
+    "$[?@.a == 1 ||]",
+    "$ ",
+    "@.a",
+  ] {
+    assert!(doc.query(query).is_err(), "{:?} should be invalid", query);
+  }
+
+  let error = doc.query("$.store[").unwrap_err();
+  assert_eq!(error.offset, 8);
+}
@@ -0,0 +1,98 @@
+extern crate json_ast;
+
+use json_ast::{parse, LocationKind, Node, Parsed, PathSegment};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+const DOC: &str = r#"{
+  "servers": [
+    { "host": "a", "port": 8080 },
+    true
+  ],
+  "name" : "api"
+}"#;
+
+fn offset_of(needle: &str) -> usize {
+  DOC.find(needle).unwrap()
+}
+
+#[test]
+fn value_under_cursor() {
+  let doc = tree(DOC);
+
+  let location = doc.node_at(DOC, offset_of("8080") + 2).unwrap();
+  assert_eq!(location.kind, LocationKind::Value);
+  assert_eq!(location.node.as_u64(), Ok(8080));
+  assert_eq!(
+    location.path,
+    vec![
+      PathSegment::Key("servers".to_string()),
+      PathSegment::Index(0),
+      PathSegment::Key("port".to_string()),
+    ]
+  );
+  assert_eq!(location.ancestors.len(), 3);
+  assert!(location.ancestors[1].as_array().is_some());
+  assert!(location.property.unwrap().key.matches("port"));
+
+  let location = doc.node_at(DOC, offset_of("true")).unwrap();
+  assert_eq!(location.node.as_bool(), Some(true));
+  assert_eq!(location.path[1], PathSegment::Index(1));
+  assert!(location.property.is_none());
+}
+
+#[test]
+fn key_under_cursor() {
+  let doc = tree(DOC);
+
+  let location = doc.node_at(DOC, offset_of("\"host\"") + 1).unwrap();
+  assert_eq!(location.kind, LocationKind::Key);
+  assert_eq!(location.node.decoded().unwrap(), "a");
+  assert!(location.property.unwrap().key.matches("host"));
+  assert_eq!(location.path.len(), 3);
+  assert!(location.ancestors.last().unwrap().as_object().is_some());
+}
+
+#[test]
+fn separators_and_whitespace() {
+  let doc = tree(DOC);
+
+  let colon = doc.node_at(DOC, offset_of(" : ") + 1).unwrap();
+  assert_eq!(colon.kind, LocationKind::Separator);
+  assert!(colon.property.unwrap().key.matches("name"));
+  assert!(colon.node.as_object().is_some());
+  assert!(colon.path.is_empty());
+
+  let before_colon = doc.node_at(DOC, offset_of(" : ")).unwrap();
+  assert_eq!(before_colon.kind, LocationKind::Whitespace);
+
+  let comma = doc.node_at(DOC, offset_of("},") + 1).unwrap();
+  assert_eq!(comma.kind, LocationKind::Separator);
+  assert!(comma.node.as_array().is_some());
+  assert!(comma.property.is_none());
+
+  let indent = doc.node_at(DOC, offset_of("  \"name\"")).unwrap();
+  assert_eq!(indent.kind, LocationKind::Whitespace);
+  assert!(indent.ancestors.is_empty());
+}
+
+#[test]
+fn container_punctuation() {
+  let doc = tree(DOC);
+
+  let bracket = doc.node_at(DOC, offset_of("[")).unwrap();
+  assert_eq!(bracket.kind, LocationKind::Value);
+  assert!(bracket.node.as_array().is_some());
+  assert!(bracket.property.unwrap().key.matches("servers"));
+
+  let brace = doc.node_at(DOC, 0).unwrap();
+  assert_eq!(brace.kind, LocationKind::Value);
+  assert!(brace.path.is_empty());
+
+  assert!(doc.node_at(DOC, DOC.len()).is_none());
+}
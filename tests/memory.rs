@@ -0,0 +1,64 @@
+extern crate json_ast;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use json_ast::{parse, Document, Parsed};
+
+// Tracks the bytes currently allocated, so the size of a structure is the
+// change in live bytes across building it, and the most ever allocated at
+// once, for the memory needed along the way
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    let live = LIVE.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+    PEAK.fetch_max(live, Ordering::SeqCst);
+    System.alloc(layout)
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+    System.dealloc(ptr, layout)
+  }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+// A Document is the compact alternative to a Node tree, which keeps its full
+// positions, so this compares the two on the same input
+#[test]
+fn documents_are_smaller_than_trees() {
+  let input = fs::read_to_string("./tests/fixtures/valid/big.json").unwrap();
+
+  let before = LIVE.load(Ordering::SeqCst);
+  PEAK.store(before, Ordering::SeqCst);
+  let tree = match parse(&input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  };
+  let tree_bytes = LIVE.load(Ordering::SeqCst) - before;
+  let tree_peak = PEAK.load(Ordering::SeqCst) - before;
+
+  let before = LIVE.load(Ordering::SeqCst);
+  let document = Document::new(&tree).unwrap();
+  let document_bytes = LIVE.load(Ordering::SeqCst) - before;
+
+  assert!(document_bytes * 2 < tree_bytes);
+  assert_eq!(document.to_node(document.root()).span(), tree.span());
+
+  // Parsing straight into a Document never needs the tree or the tokens
+  let before = LIVE.load(Ordering::SeqCst);
+  PEAK.store(before, Ordering::SeqCst);
+  let parsed = Document::parse(&input).unwrap();
+  let parsed_peak = PEAK.load(Ordering::SeqCst) - before;
+
+  assert!(parsed_peak < tree_bytes);
+  assert!(parsed_peak * 3 < tree_peak);
+  assert_eq!(parsed.span(parsed.root()), tree.span());
+}
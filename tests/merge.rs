@@ -0,0 +1,94 @@
+extern crate json_ast;
+
+use json_ast::{canonicalize, merge_patch, merge_patches, parse, Node, NodeId, Parsed, TreeIndex};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+#[test]
+fn rfc_examples() {
+  let cases = [
+    (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+    (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+    (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+    (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+    (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+    (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+    (r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#, r#"{"a":{"b":"d"}}"#),
+    (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+    (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+    (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+    (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+    (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+    (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+    (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+    (r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+  ];
+  for &(base, patch, expected) in &cases {
+    let merged = merge_patch(&tree(base), &tree(patch));
+    assert_eq!(canonicalize(&merged.tree), canonicalize(&tree(expected)), "{} + {}", base, patch);
+    assert_eq!(merged.origins.len(), TreeIndex::new(&merged.tree).len());
+  }
+}
+
+const BASE: &str = r#"{
+  "server": {
+    "host": "0.0.0.0",
+    "port": 8080
+  },
+  "features": ["auth"],
+  "debug": false
+}"#;
+
+const ENVIRONMENT: &str = r#"{
+  "server": {"host": "prod.internal", "tls": {"cert": "/etc/cert.pem"}},
+  "features": ["auth", "billing"]
+}"#;
+
+const OVERRIDES: &str = r#"{
+  "server": {
+    "port": 9090,
+    "tls": null
+  },
+  "debug": null
+}"#;
+
+#[test]
+fn origins_name_the_layer_and_span() {
+  let (base, environment, overrides) = (tree(BASE), tree(ENVIRONMENT), tree(OVERRIDES));
+  let layers = [BASE, ENVIRONMENT, OVERRIDES];
+  let merged = merge_patches(&base, &[&environment, &overrides]);
+  assert_eq!(
+    canonicalize(&merged.tree).unwrap(),
+    r#"{"features":["auth","billing"],"server":{"host":"prod.internal","port":9090}}"#
+  );
+
+  let origin = |pointer: &str| {
+    let origin = merged.origin_at(pointer).unwrap();
+    let start = origin.span.start;
+    (origin.source, start.line, start.column, origin.span.text(layers[origin.source]).to_string())
+  };
+  assert_eq!(origin(""), (0, 1, 1, BASE.to_string()));
+  assert_eq!(origin("/server").0, 0);
+  assert_eq!(origin("/server/host"), (1, 2, 22, "\"prod.internal\"".to_string()));
+  assert_eq!(origin("/server/port"), (2, 3, 13, "9090".to_string()));
+  assert_eq!(origin("/features/1"), (1, 3, 24, "\"billing\"".to_string()));
+  assert_eq!(merged.origin_at("/debug"), None);
+
+  // Members keep the key and span from the layer their value came from
+  let index = TreeIndex::new(&merged.tree);
+  let port = index.id_of(merged.tree.pointer("/server/port").unwrap()).unwrap();
+  let property = index.property(port).unwrap();
+  assert_eq!(property.span.text(OVERRIDES), "\"port\": 9090");
+  assert_eq!(merged.origin(port).source, 2);
+  for id in 0..index.len() {
+    let pointer = merged.tree.pointer_of(index.node(NodeId(id))).unwrap();
+    assert_eq!(merged.origin_at(&pointer), Some(merged.origin(NodeId(id))), "{}", pointer);
+  }
+  assert_eq!(merged.origin_at("/features/2"), None);
+  assert_eq!(merged.origin_at("/features/01"), None);
+}
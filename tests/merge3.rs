@@ -0,0 +1,118 @@
+extern crate json_ast;
+
+use json_ast::{merge3, parse_jsonc, Merge3Error, Node, Parsed};
+
+fn tree(input: &str) -> Node {
+  match parse_jsonc(input).0 {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+const BASE: &str = r#"{
+  "name": "api",
+  "port": 80,
+  "tls": {"enabled": false},
+  "tags": ["a"],
+  "owner": "infra"
+}"#;
+
+// Reformatted with comments, and the port and owner changed
+const OURS: &str = r#"{
+    // The public API
+    "name": "api",
+    "port": 8080, // behind the proxy
+    "tls": {
+        "enabled": false
+    },
+    "tags": ["a"]
+}"#;
+
+#[test]
+fn takes_their_changes_into_our_text() {
+  let theirs = r#"{
+    "name": "api", "port": 80, "tls": {"enabled": true, "ca": "/ca.pem"},
+    "tags": ["a", "b"], "owner": "infra", "replicas": 3
+  }"#;
+  let merged = merge3(&tree(BASE), OURS, &tree(OURS), &tree(theirs)).unwrap();
+  assert!(merged.conflicts.is_empty());
+  assert_eq!(
+    merged.text,
+    r#"{
+    // The public API
+    "name": "api",
+    "port": 8080, // behind the proxy
+    "tls": {
+        "enabled": true,
+        "ca": "/ca.pem"
+    },
+    "tags": ["a", "b"],
+    "replicas": 3
+}"#
+  );
+  assert_eq!(merged.tree.pointer("/replicas").unwrap().as_u64(), Ok(3));
+}
+
+#[test]
+fn reports_conflicts_and_keeps_ours() {
+  let theirs = r#"{
+    "name": "web", "port": 443, "tls": {"enabled": false}, "tags": ["a"], "owner": "platform"
+  }"#;
+  let merged = merge3(&tree(BASE), OURS, &tree(OURS), &tree(theirs)).unwrap();
+  assert_eq!(
+    merged.text,
+    OURS.replace(r#""name": "api""#, r#""name": "web""#)
+  );
+
+  let conflicts: Vec<_> = merged
+    .conflicts
+    .iter()
+    .map(|conflict| {
+      (
+        conflict.pointer.clone(),
+        conflict.base.map(|span| span.text(BASE)),
+        conflict.ours.map(|span| span.text(OURS)),
+        conflict.theirs.map(|span| span.text(theirs)),
+      )
+    })
+    .collect();
+  assert_eq!(
+    conflicts,
+    vec![
+      ("/port".to_string(), Some("80"), Some("8080"), Some("443")),
+      ("/owner".to_string(), Some(r#""infra""#), None, Some(r#""platform""#)),
+    ]
+  );
+}
+
+#[test]
+fn same_change_on_both_sides() {
+  let base = r#"{"a": 1}"#;
+  let ours = r#"{"a": 2, "b": {"x": 1}}"#;
+  let theirs = r#"{"a": 2, "b": {"y": 2}}"#;
+  let merged = merge3(&tree(base), ours, &tree(ours), &tree(theirs)).unwrap();
+  assert!(merged.conflicts.is_empty());
+  assert_eq!(merged.text, r#"{"a": 2, "b": {"x": 1, "y": 2}}"#);
+
+  // A member both sides removed stays removed
+  let merged = merge3(&tree(base), "{}", &tree("{}"), &tree("{}")).unwrap();
+  assert_eq!(merged.text, "{}");
+}
+
+#[test]
+fn text_that_isnt_ours() {
+  let (base, theirs) = (tree(r#"{"a": 1}"#), tree(r#"{"a": 2}"#));
+  assert_eq!(
+    merge3(&base, r#"{"a": 1} @"#, &base, &theirs).unwrap_err(),
+    Merge3Error::Unparsable("/a".to_string())
+  );
+  assert_eq!(merge3(&base, "{}", &base, &theirs).unwrap_err(), Merge3Error::SourceMismatch);
+  assert_eq!(merge3(&base, r#"{"a": [1,"#, &base, &theirs).unwrap_err(), Merge3Error::SourceMismatch);
+
+  // The same values at the same places, with a colon for a comma between
+  let (base, theirs) = (tree(r#"{"a": 1, "b": 2}"#), tree(r#"{"a": 3, "b": 2}"#));
+  assert_eq!(
+    merge3(&base, r#"{"a": 1: "b": 2}"#, &base, &theirs).unwrap_err(),
+    Merge3Error::Unparsable("/a".to_string())
+  );
+}
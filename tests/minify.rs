@@ -0,0 +1,93 @@
+extern crate json_ast;
+
+use std::fs;
+use std::io;
+
+use json_ast::{minify, minify_str, minify_to, parse, Document, Node, ParseErrorType, Parsed};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+const DOC: &str = r#"{
+  "name" : "café",
+  "values" : [ 1.50, -0, 1E+3, true, null ],
+  "empty" : { }
+}"#;
+
+#[test]
+fn lexemes_are_kept() {
+  let expected = r#"{"name":"café","values":[1.50,-0,1E+3,true,null],"empty":{}}"#;
+  assert_eq!(minify(&tree(DOC)), expected);
+  assert_eq!(minify_str(DOC, false).unwrap(), expected);
+}
+
+#[test]
+fn comments() {
+  let input = "/* header */ {\n  \"a\": 1, // one\n  \"b\": [ /* none */ ]\n}";
+  assert_eq!(minify_str(input, false).unwrap(), r#"{"a":1,"b":[]}"#);
+  assert_eq!(
+    minify_str(input, true).unwrap(),
+    "/* header */{\"a\":1,// one\n\"b\":[/* none */]}"
+  );
+}
+
+#[test]
+fn writes_as_it_goes() {
+  let mut out = vec![];
+  minify_to("[1, 2]", false, &mut out).unwrap();
+  assert_eq!(out, b"[1,2]");
+}
+
+#[test]
+fn invalid_text_is_an_error() {
+  let mut out = vec![];
+  let error = minify_to("[1, @]", false, &mut out).unwrap_err();
+  assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+  assert_eq!(out, b"[1,");
+
+  let error = minify_str("{\n  \"a\": tru }", false).unwrap_err();
+  assert!(matches!(error.error_type, ParseErrorType::InvalidToken));
+  assert_eq!((error.position.line, error.position.column), (2, 8));
+
+  match parse("[1, @]") {
+    Parsed::Failure {
+      tree: None, errors, ..
+    } => {
+      assert_eq!(errors.len(), 1);
+      assert!(matches!(errors[0].error_type, ParseErrorType::InvalidToken));
+      assert_eq!(errors[0].position.index, 4);
+    }
+    _ => panic!("expected a failure"),
+  }
+}
+
+#[test]
+fn lone_minus_is_an_error() {
+  for input in &["[-", "{\"a\":-"] {
+    let error = minify_str(input, false).unwrap_err();
+    assert!(matches!(error.error_type, ParseErrorType::InvalidToken));
+    assert_eq!(error.position.index, input.len() - 1);
+    assert!(Document::parse(input).is_err());
+    match parse(input) {
+      Parsed::Failure { tree: None, .. } => {}
+      _ => panic!("expected a failure"),
+    }
+  }
+}
+
+#[test]
+fn tree_and_token_minifiers_agree() {
+  for entry in fs::read_dir("./tests/fixtures/valid/").unwrap() {
+    let path = entry.unwrap().path();
+    let input = fs::read_to_string(&path).unwrap();
+    if let Parsed::Success { tree } = parse(&input) {
+      let minified = minify(&tree);
+      assert_eq!(minified, minify_str(&input, false).unwrap(), "{:?}", path);
+      assert_eq!(minify(&self::tree(&minified)), minified, "{:?}", path);
+    }
+  }
+}
@@ -0,0 +1,75 @@
+extern crate json_ast;
+
+use json_ast::{parse, Node, Parsed};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+const CONFIG: &str = r#"{
+  "name": "api",
+  "debug": false,
+  "proxy": null,
+  "servers": [
+    { "host": "a.example", "port": 8080 },
+    { "host": "b.example", "port": 8081 }
+  ]
+}"#;
+
+#[test]
+fn typed_accessors() {
+  let config = tree(CONFIG);
+
+  assert_eq!(config.as_object().unwrap().len(), 4);
+  assert!(config.as_array().is_none());
+  assert_eq!(config.get("debug").unwrap().as_bool(), Some(false));
+  assert!(config.get("proxy").unwrap().is_null());
+  assert!(!config.get("name").unwrap().is_null());
+  assert!(config.get("missing").is_none());
+
+  let servers = config.get("servers").unwrap();
+  assert_eq!(servers.as_array().unwrap().len(), 2);
+  assert!(servers.get_index(2).is_none());
+  assert_eq!(servers.get_index(1).unwrap().get("port").unwrap().as_u64(), Ok(8081));
+}
+
+#[test]
+fn keys_and_entries() {
+  let config = tree(CONFIG);
+
+  let keys: Vec<String> = config.keys().map(|key| key.decoded().into_owned()).collect();
+  assert_eq!(keys, vec!["name", "debug", "proxy", "servers"]);
+
+  let (key, value) = config.entries().nth(1).unwrap();
+  assert_eq!(key.span().start.line, 3);
+  assert_eq!(value.as_bool(), Some(false));
+
+  assert_eq!(config["servers"].keys().count(), 0);
+}
+
+#[test]
+fn index_operators() {
+  let config = tree(CONFIG);
+
+  let port = &config["servers"][0]["port"];
+  assert_eq!(port.as_i64(), Ok(8080));
+  assert_eq!(port.span().start.line, 6);
+  assert_eq!(config["name"].decoded().unwrap(), "api");
+}
+
+#[test]
+#[should_panic(expected = "no member \"missing\"")]
+fn index_missing_key() {
+  let config = tree(CONFIG);
+  let _ = &config["missing"];
+}
+
+#[test]
+#[should_panic(expected = "no element 5")]
+fn index_missing_element() {
+  let config = tree(CONFIG);
+  let _ = &config["servers"][5];
+}
@@ -0,0 +1,144 @@
+extern crate json_ast;
+
+use json_ast::{check_precision, parse, Decimal, Node, NumberError, Parsed, PrecisionIssue};
+
+fn number(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+#[test]
+fn integer_accessors() {
+  assert_eq!(number("8080").as_u64(), Ok(8080));
+  assert_eq!(number("-0").as_i64(), Ok(0));
+  assert_eq!(number("1e2").as_i64(), Ok(100));
+  assert_eq!(number("1.50e1").as_i64(), Ok(15));
+  assert_eq!(number("-9223372036854775808").as_i64(), Ok(i64::MIN));
+  assert_eq!(number("18446744073709551615").as_u64(), Ok(u64::MAX));
+  assert_eq!(
+    number("-170141183460469231731687303715884105728").as_i128(),
+    Ok(i128::MIN)
+  );
+}
+
+#[test]
+fn integer_accessor_errors() {
+  assert_eq!(number("9223372036854775808").as_i64(), Err(NumberError::OutOfRange));
+  assert_eq!(number("-1").as_u64(), Err(NumberError::OutOfRange));
+  assert_eq!(number("1.5").as_i64(), Err(NumberError::Fractional));
+  assert_eq!(number("15e-1").as_i64(), Err(NumberError::Fractional));
+  assert_eq!(number("1e400").as_i128(), Err(NumberError::OutOfRange));
+  assert_eq!(number("\"1\"").as_i64(), Err(NumberError::NotANumber));
+}
+
+#[test]
+fn float_accessor() {
+  assert_eq!(number("0.5").as_f64(), Ok(0.5));
+  assert_eq!(number("1E3").as_f64(), Ok(1000.0));
+  assert!(number("-0").as_f64().unwrap().is_sign_negative());
+  assert_eq!(number("1E400").as_f64(), Err(NumberError::Overflow));
+  assert_eq!(number("-1e400").as_f64(), Err(NumberError::Overflow));
+  assert_eq!(number("1e-400").as_f64(), Err(NumberError::Underflow));
+  assert_eq!(number("0e-400").as_f64(), Ok(0.0));
+}
+
+#[test]
+fn inexact_floats() {
+  assert_eq!(number("0.1").as_f64(), Err(NumberError::Inexact));
+  assert_eq!(number("0.1").as_f64_rounded(), Ok(0.1));
+  assert_eq!(number("9007199254740993").as_f64(), Err(NumberError::Inexact));
+  assert_eq!(number("9007199254740993").as_f64_rounded(), Ok(9007199254740992.0));
+  assert_eq!(number("9007199254740992").as_f64(), Ok(9007199254740992.0));
+  assert_eq!(number("0.125").as_f64(), Ok(0.125));
+  assert_eq!(number("-1.5e300").as_f64(), Err(NumberError::Inexact));
+  assert_eq!(number("1e300").as_f64(), Err(NumberError::Inexact));
+  assert_eq!(number("1e22").as_f64(), Ok(1e22));
+  assert_eq!(number("5e-324").as_f64(), Err(NumberError::Inexact));
+  assert_eq!(
+    number("4.940656458412465441765687928682213723651e-324").as_f64_rounded(),
+    Ok(5e-324)
+  );
+}
+
+#[test]
+fn exact_decimal() {
+  assert_eq!(
+    number("-12.3400e+2").decimal(),
+    Ok(Decimal {
+      negative: true,
+      mantissa: "1234".to_string(),
+      exponent: 0,
+    })
+  );
+  assert_eq!(number("1.0").decimal(), number("1").decimal());
+  assert_eq!(
+    number("100").decimal(),
+    Ok(Decimal {
+      negative: false,
+      mantissa: "1".to_string(),
+      exponent: 2,
+    })
+  );
+  assert!(number("-0.0").decimal().unwrap().negative);
+  assert!(number("-0.0").decimal().unwrap().is_zero());
+  assert_eq!(
+    number("1e99999999999999999999").decimal(),
+    Err(NumberError::Overflow)
+  );
+}
+
+#[test]
+fn precision_warnings() {
+  let tree = number(
+    r#"{
+  "id": 12345678901234567890,
+  "safe": [9007199254740991, -9007199254740991, 0.1, 1.5e300, -0],
+  "unsafe": -9007199254740993,
+  "inexact": 0.10000000000000001,
+  "huge": 1e400,
+  "tiny": -1e-400
+}"#,
+  );
+
+  let warnings = check_precision(&tree);
+  let summary: Vec<(PrecisionIssue, &str, usize)> = warnings
+    .iter()
+    .map(|w| (w.issue, &w.raw[..], w.span.start.line))
+    .collect();
+  assert_eq!(
+    summary,
+    vec![
+      (PrecisionIssue::UnsafeInteger, "12345678901234567890", 2),
+      (PrecisionIssue::UnsafeInteger, "-9007199254740993", 4),
+      (PrecisionIssue::Inexact, "0.10000000000000001", 5),
+      (PrecisionIssue::Overflow, "1e400", 6),
+      (PrecisionIssue::Underflow, "-1e-400", 7),
+    ]
+  );
+
+  assert_eq!(warnings[0].value, 12345678901234567000.0);
+  assert_eq!(warnings[0].span.start.column, 9);
+  assert_eq!(warnings[0].span.end.column, 29);
+  assert_eq!(warnings[1].value, -9007199254740992.0);
+  assert_eq!(warnings[2].value, 0.1);
+  assert!(warnings[3].value.is_infinite());
+  assert!(warnings[4].value == 0.0 && warnings[4].value.is_sign_negative());
+}
+
+#[test]
+fn precision_integer_boundary() {
+  let tree = number("[9007199254740992, -9007199254740992, 9007199254740993, -9007199254740994]");
+  let warnings: Vec<_> = check_precision(&tree)
+    .into_iter()
+    .map(|w| (w.issue, w.raw))
+    .collect();
+  assert_eq!(
+    warnings,
+    vec![
+      (PrecisionIssue::UnsafeInteger, "9007199254740993".to_string()),
+      (PrecisionIssue::UnsafeInteger, "-9007199254740994".to_string()),
+    ]
+  );
+}
@@ -0,0 +1,209 @@
+extern crate json_ast;
+
+use json_ast::{apply_edits, apply_patch, canonicalize, format, parse, parse_jsonc, EditError,
+               FormatOptions, Node, Parsed, PatchError};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+fn jsonc(input: &str) -> Node {
+  match parse_jsonc(input).0 {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+fn patch(src: &str, operations: &str) -> Result<String, PatchError> {
+  let (patched, edits) = apply_patch(src, &jsonc(src), &tree(operations))?;
+  let text = apply_edits(src, &edits).unwrap();
+  let options = FormatOptions::default();
+  assert_eq!(format(&patched, &options), format(&jsonc(&text), &options));
+  Ok(text)
+}
+
+const CONFIG: &str = r#"{
+  // Service
+  "name": "api",
+  "replicas": 2,
+  "ports": [80, 443],
+  "env": {
+    "LOG": "info", // or debug
+    "TZ": "UTC"
+  }
+}
+"#;
+
+#[test]
+fn operations() {
+  let add = r#"[{"op": "add", "path": "/env/REGION", "value": "eu"}]"#;
+  assert_eq!(
+    patch(CONFIG, add).unwrap(),
+    CONFIG.replace(r#""TZ": "UTC""#, "\"TZ\": \"UTC\",\n    \"REGION\": \"eu\"")
+  );
+
+  let remove = r#"[{"op": "remove", "path": "/replicas"}]"#;
+  assert_eq!(patch(CONFIG, remove).unwrap(), CONFIG.replace("  \"replicas\": 2,\n", ""));
+
+  let replace = r#"[{"op": "replace", "path": "/ports/1", "value": 8443}]"#;
+  assert_eq!(patch(CONFIG, replace).unwrap(), CONFIG.replace("443", "8443"));
+
+  let copy = r#"[{"op": "copy", "from": "/ports/0", "path": "/ports/0"}]"#;
+  assert_eq!(patch(CONFIG, copy).unwrap(), CONFIG.replace("[80, 443]", "[80, 80, 443]"));
+
+  let test = r#"[{"op": "test", "path": "/env", "value": {"TZ": "UTC", "LOG": "info"}}]"#;
+  assert_eq!(patch(CONFIG, test).unwrap(), CONFIG);
+
+  let moved = r#"[{"op": "move", "from": "/env/LOG", "path": "/log"}]"#;
+  assert_eq!(
+    patch(CONFIG, moved).unwrap(),
+    r#"{
+  // Service
+  "name": "api",
+  "replicas": 2,
+  "ports": [80, 443],
+  "env": {
+    "TZ": "UTC"
+  },
+  "log": "info"
+}
+"#
+  );
+}
+
+#[test]
+fn edits_are_against_the_original() {
+  let operations = r#"[
+    {"op": "replace", "path": "/replicas", "value": 3},
+    {"op": "add", "path": "/ports/-", "value": 8080},
+    {"op": "remove", "path": "/ports/0"},
+    {"op": "replace", "path": "/replicas", "value": 2},
+    {"op": "replace", "path": "/name", "value": "web"}
+  ]"#;
+  let (_, edits) = apply_patch(CONFIG, &jsonc(CONFIG), &tree(operations)).unwrap();
+  // The two replicas replacements cancel out
+  let changed: Vec<&str> = edits.iter().map(|edit| &CONFIG[edit.start..edit.end]).collect();
+  assert_eq!(changed, vec!["\"api\"", "80, ", ""]);
+  assert_eq!(
+    apply_edits(CONFIG, &edits).unwrap(),
+    CONFIG.replace("\"api\"", "\"web\"").replace("[80, 443]", "[443, 8080]")
+  );
+}
+
+#[test]
+fn rfc_examples() {
+  let cases = [
+    (
+      r#"{"foo": "bar"}"#,
+      r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+      r#"{"baz": "qux", "foo": "bar"}"#,
+    ),
+    (
+      r#"{"foo": ["bar", "baz"]}"#,
+      r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+      r#"{"foo": ["bar", "qux", "baz"]}"#,
+    ),
+    (
+      r#"{"baz": "qux", "foo": "bar"}"#,
+      r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+      r#"{"baz": "boo", "foo": "bar"}"#,
+    ),
+    (
+      r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+      r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+      r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+    ),
+    (
+      r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+      r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+      r#"{"foo": ["all", "cows", "eat", "grass"]}"#,
+    ),
+    (
+      r#"{"foo": "bar"}"#,
+      r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#,
+      r#"{"foo": "bar", "child": {"grandchild": {}}}"#,
+    ),
+    (
+      r#"{"foo": ["bar"]}"#,
+      r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+      r#"{"foo": ["bar", ["abc", "def"]]}"#,
+    ),
+    (
+      r#"{"/": 9, "~1": 10}"#,
+      r#"[{"op": "test", "path": "/~01", "value": 10}]"#,
+      r#"{"/": 9, "~1": 10}"#,
+    ),
+  ];
+  for &(src, operations, expected) in &cases {
+    let text = patch(src, operations).unwrap();
+    assert_eq!(canonicalize(&tree(&text)), canonicalize(&tree(expected)), "{}", operations);
+  }
+}
+
+#[test]
+fn errors() {
+  let src = r#"{"foo": {"bar": 1}, "list": [1, 2]}"#;
+  let error = |operations: &str| apply_patch(src, &tree(src), &tree(operations)).unwrap_err();
+
+  let operations = r#"[{"op": "test", "path": "/list/1", "value": "2"}]"#;
+  match error(operations) {
+    PatchError::TestFailed(span) => {
+      assert_eq!(span.text(operations), &operations[1..operations.len() - 1])
+    }
+    other => panic!("unexpected {:?}", other),
+  }
+  let operations = r#"[{"op": "add", "path": "/a", "value": 1}, {"op": "delete", "path": "/a"}]"#;
+  match error(operations) {
+    PatchError::InvalidOperation(span) => assert_eq!(span.text(operations), r#""delete""#),
+    other => panic!("unexpected {:?}", other),
+  }
+  let operations = r#"[{"op": "add", "path": "/a"}]"#;
+  match error(operations) {
+    PatchError::InvalidOperation(span) => {
+      assert_eq!(span.text(operations), r#"{"op": "add", "path": "/a"}"#)
+    }
+    other => panic!("unexpected {:?}", other),
+  }
+
+  assert!(matches!(
+    error(r#"[{"op": "remove", "path": "/baz"}]"#),
+    PatchError::Edit(_, EditError::NotFound)
+  ));
+  assert!(matches!(
+    error(r#"[{"op": "replace", "path": "/list/2", "value": 3}]"#),
+    PatchError::Edit(_, EditError::NotFound)
+  ));
+  assert!(matches!(
+    error(r#"[{"op": "add", "path": "foo", "value": 3}]"#),
+    PatchError::Edit(_, EditError::InvalidPointer)
+  ));
+  assert!(matches!(
+    error(r#"[{"op": "move", "from": "/foo", "path": "/foo/bar/x"}]"#),
+    PatchError::InvalidOperation(_)
+  ));
+  assert!(matches!(error(r#"{"op": "remove", "path": "/foo"}"#), PatchError::InvalidOperation(_)));
+
+  // Text that isn't what the tree was parsed from
+  let operations = r#"[{"op": "replace", "path": "", "value": 2}]"#;
+  assert!(matches!(
+    apply_patch("1 @", &tree("1"), &tree(operations)),
+    Err(PatchError::Unparsable(_))
+  ));
+}
+
+#[test]
+fn source_that_isnt_the_tree() {
+  let operations = tree(r#"[{"op": "replace", "path": "/a", "value": 3}]"#);
+  assert!(matches!(
+    apply_patch("{}", &tree(r#"{"a": 1}"#), &operations),
+    Err(PatchError::Edit(_, EditError::SourceMismatch))
+  ));
+  // The same values at the same places, with a colon for a comma between
+  assert!(matches!(
+    apply_patch(r#"{"a": 1: "b": 2}"#, &tree(r#"{"a": 1, "b": 2}"#), &operations),
+    Err(PatchError::Unparsable(_))
+  ));
+}
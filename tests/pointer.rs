@@ -0,0 +1,79 @@
+extern crate json_ast;
+
+use json_ast::{escape_pointer, parse, to_pointer, Node, Parsed, PathSegment};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+const DOC: &str = r#"{
+  "servers": [
+    { "host": "a", "port": 8080 },
+    { "host": "b", "port": 8081 }
+  ],
+  "a/b": 1,
+  "m~n": 2,
+  "café": 3,
+  "": { "": 4 },
+  "10": 5
+}"#;
+
+#[test]
+fn resolves_pointers() {
+  let doc = tree(DOC);
+
+  let port = doc.pointer("/servers/1/port").unwrap();
+  assert_eq!(port.as_u64(), Ok(8081));
+  assert_eq!(port.span().start.line, 4);
+
+  assert!(doc.pointer("").unwrap().as_object().is_some());
+  assert_eq!(doc.pointer("/a~1b").unwrap().as_i64(), Ok(1));
+  assert_eq!(doc.pointer("/m~0n").unwrap().as_i64(), Ok(2));
+  assert_eq!(doc.pointer("/café").unwrap().as_i64(), Ok(3));
+  assert_eq!(doc.pointer("//").unwrap().as_i64(), Ok(4));
+  assert_eq!(doc.pointer("/10").unwrap().as_i64(), Ok(5));
+}
+
+#[test]
+fn rejects_invalid_pointers() {
+  let doc = tree(DOC);
+
+  assert!(doc.pointer("servers").is_none());
+  assert!(doc.pointer("/servers/01").is_none());
+  assert!(doc.pointer("/servers/-").is_none());
+  assert!(doc.pointer("/servers/2").is_none());
+  assert!(doc.pointer("/servers/0/port/x").is_none());
+  assert!(doc.pointer("/m~2n").is_none());
+  assert!(doc.pointer("/a/b").is_none());
+}
+
+#[test]
+fn pointer_of_nodes() {
+  let doc = tree(DOC);
+
+  let port = doc.pointer("/servers/0/port").unwrap();
+  assert_eq!(doc.pointer_of(port).unwrap(), "/servers/0/port");
+  assert_eq!(doc.pointer_of(&doc).unwrap(), "");
+
+  let slash = doc.pointer("/a~1b").unwrap();
+  assert_eq!(doc.pointer_of(slash).unwrap(), "/a~1b");
+  assert_eq!(
+    doc.path_of(doc.pointer("/m~0n").unwrap()).unwrap(),
+    vec![PathSegment::Key("m~n".to_string())]
+  );
+
+  let other = tree(DOC);
+  assert!(doc.pointer_of(other.pointer("/servers").unwrap()).is_none());
+}
+
+#[test]
+fn pointer_escaping() {
+  assert_eq!(escape_pointer("a/~b"), "a~1~0b");
+  assert_eq!(
+    to_pointer(&[PathSegment::Key("x/y".to_string()), PathSegment::Index(3)]),
+    "/x~1y/3"
+  );
+}
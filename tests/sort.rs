@@ -0,0 +1,124 @@
+extern crate json_ast;
+
+use json_ast::{apply_edits, check_key_order, parse_jsonc, sort_keys, EditError, KeyOrder, Node,
+               Parsed, SortOptions};
+
+fn tree(input: &str) -> Node {
+  match parse_jsonc(input).0 {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+fn sort(src: &str, options: &SortOptions) -> String {
+  apply_edits(src, &sort_keys(src, &tree(src), options).unwrap()).unwrap()
+}
+
+const SETTINGS: &str = r#"{
+  // Window
+  "window.zoom": 1, // percent
+  "editor": {
+    "tabSize": 2,
+    "rulers": [80]
+  },
+  /* Files */
+  "files": [{"z": 1, "a": 2}],
+  "color": "dark"
+}"#;
+
+#[test]
+fn members_move_with_their_comments() {
+  assert_eq!(
+    sort(SETTINGS, &SortOptions::default()),
+    r#"{
+  "color": "dark",
+  "editor": {
+    "rulers": [80],
+    "tabSize": 2
+  },
+  /* Files */
+  "files": [{"a": 2, "z": 1}],
+  // Window
+  "window.zoom": 1 // percent
+}"#
+  );
+
+  let options = SortOptions {
+    recursive: false,
+    ..SortOptions::default()
+  };
+  assert_eq!(
+    sort(SETTINGS, &options),
+    r#"{
+  "color": "dark",
+  "editor": {
+    "tabSize": 2,
+    "rulers": [80]
+  },
+  /* Files */
+  "files": [{"z": 1, "a": 2}],
+  // Window
+  "window.zoom": 1 // percent
+}"#
+  );
+}
+
+#[test]
+fn sorted_objects_are_left_alone() {
+  let src = r#"{"a": {"c": 1, "b": 2}, "b": [], "c": {"x": 1}}"#;
+  let edits = sort_keys(src, &tree(src), &SortOptions::default()).unwrap();
+  assert_eq!(edits.len(), 1);
+  assert_eq!(&src[edits[0].start..edits[0].end], r#""c": 1, "b": 2"#);
+  assert_eq!(apply_edits(src, &edits).unwrap(), r#"{"a": {"b": 2, "c": 1}, "b": [], "c": {"x": 1}}"#);
+
+  let src = r#"{"a": 1, "a": 0, "b": 2,}"#;
+  assert!(sort_keys(src, &tree(src), &SortOptions::default()).unwrap().is_empty());
+
+  assert_eq!(
+    sort_keys("{}", &tree(r#"{"b": 1, "a": 2}"#), &SortOptions::default()),
+    Err(EditError::SourceMismatch)
+  );
+}
+
+#[test]
+fn orders() {
+  let src = r#"{"item10": 0, "Item2": 0, "item1": 0, "id": 0}"#;
+  let natural = SortOptions {
+    order: KeyOrder::Natural,
+    ..SortOptions::default()
+  };
+  assert_eq!(sort(src, &natural), r#"{"id": 0, "item1": 0, "Item2": 0, "item10": 0}"#);
+  assert_eq!(
+    sort(src, &SortOptions::default()),
+    r#"{"Item2": 0, "id": 0, "item1": 0, "item10": 0}"#
+  );
+
+  let src = r#"{"scripts": {}, "version": "1.0.0", "dependencies": {}, "name": "app"}"#;
+  let custom = SortOptions {
+    order: KeyOrder::Custom(vec!["name".to_string(), "version".to_string()]),
+    recursive: false,
+  };
+  assert_eq!(
+    sort(src, &custom),
+    r#"{"name": "app", "version": "1.0.0", "dependencies": {}, "scripts": {}}"#
+  );
+}
+
+#[test]
+fn check_mode() {
+  let options = SortOptions::default();
+  let span = check_key_order(&tree(SETTINGS), &options).unwrap();
+  assert_eq!(span.text(SETTINGS), r#""editor""#);
+
+  let src = r#"{"a": {"d": 1, "c": 2}, "z": [{"y": 1, "x": 2}], "b": 0}"#;
+  let span = check_key_order(&tree(src), &options).unwrap();
+  assert_eq!((span.text(src), span.start.column), (r#""c""#, 16));
+  let options = SortOptions {
+    recursive: false,
+    ..SortOptions::default()
+  };
+  assert_eq!(check_key_order(&tree(src), &options).unwrap().text(src), r#""b""#);
+
+  let sorted = sort(SETTINGS, &SortOptions::default());
+  assert_eq!(check_key_order(&tree(&sorted), &SortOptions::default()), None);
+}
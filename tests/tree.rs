@@ -0,0 +1,89 @@
+extern crate json_ast;
+
+use json_ast::{parse, Node, NodeId, Parsed, PathSegment, TreeIndex};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+const DOC: &str = r#"{
+  "name": "api",
+  "servers": [
+    { "host": "a", "port": 8080 },
+    { "host": "b", "port": 8081 }
+  ],
+  "debug": false
+}"#;
+
+#[test]
+fn ids_are_in_document_order() {
+  let doc = tree(DOC);
+  let index = TreeIndex::new(&doc);
+
+  assert_eq!(index.len(), 10);
+  assert_eq!(index.root(), NodeId(0));
+  assert!(index.parent(index.root()).is_none());
+  assert_eq!(index.depth(index.root()), 0);
+
+  let depths: Vec<usize> = index.ids().map(|id| index.depth(id)).collect();
+  assert_eq!(depths, vec![0, 1, 1, 2, 3, 3, 2, 3, 3, 1]);
+}
+
+#[test]
+fn parents_and_positions() {
+  let doc = tree(DOC);
+  let index = TreeIndex::new(&doc);
+
+  let port = doc.pointer("/servers/1/port").unwrap();
+  let id = index.id_of(port).unwrap();
+  assert!(std::ptr::eq(index.node(id), port));
+  assert_eq!(index.depth(id), 3);
+  assert_eq!(index.index_in_parent(id), Some(1));
+  assert_eq!(index.key_in_parent(id).unwrap().decoded(), "port");
+  assert_eq!(
+    index.path(id),
+    vec![
+      PathSegment::Key("servers".to_string()),
+      PathSegment::Index(1),
+      PathSegment::Key("port".to_string()),
+    ]
+  );
+
+  let server = index.parent(id).unwrap();
+  assert_eq!(index.index_in_parent(server), Some(1));
+  assert!(index.key_in_parent(server).is_none());
+  assert!(index.property(server).is_none());
+  assert_eq!(index.children(server).len(), 2);
+
+  let ancestors: Vec<&Node> = index.ancestors(id).map(|id| index.node(id)).collect();
+  assert_eq!(ancestors.len(), 3);
+  assert!(std::ptr::eq(ancestors[2], &doc));
+  assert!(std::ptr::eq(ancestors[1], doc.get("servers").unwrap()));
+
+  // Only nodes borrowed from the indexed tree have ids
+  let copy = tree(DOC);
+  assert!(index.id_of(copy.pointer("/name").unwrap()).is_none());
+}
+
+#[test]
+fn siblings() {
+  let doc = tree(DOC);
+  let index = TreeIndex::new(&doc);
+
+  let servers = index.id_of(doc.get("servers").unwrap()).unwrap();
+  let name = index.prev_sibling(servers).unwrap();
+  let debug = index.next_sibling(servers).unwrap();
+  assert_eq!(index.node(name).decoded().unwrap(), "api");
+  assert_eq!(index.node(debug).as_bool(), Some(false));
+  assert!(index.prev_sibling(name).is_none());
+  assert!(index.next_sibling(debug).is_none());
+  assert_eq!(index.siblings(servers).collect::<Vec<_>>(), vec![name, debug]);
+
+  let root = index.root();
+  assert_eq!(index.siblings(root).count(), 0);
+  assert!(index.next_sibling(root).is_none());
+  assert!(index.index_in_parent(root).is_none());
+}
@@ -0,0 +1,99 @@
+extern crate json_ast;
+
+use json_ast::{parse, walk_object, walk_property_mut, Identifier, Node, Parsed, Property, Visitor,
+               VisitorMut};
+
+fn tree(input: &str) -> Node {
+  match parse(input) {
+    Parsed::Success { tree } => tree,
+    _ => panic!("expected a tree"),
+  }
+}
+
+const DOC: &str = r#"{
+  "user": { "name": "ada", "password": "hunter2" },
+  "tags": ["a", "b", 3],
+  "db": { "password": "s3cret", "port": 5432, "tls": true, "ca": null }
+}"#;
+
+struct Counter {
+  keys: Vec<String>,
+  strings: usize,
+  numbers: usize,
+  objects: usize,
+  others: usize,
+}
+
+impl Visitor for Counter {
+  fn visit_object(&mut self, node: &Node) {
+    self.objects += 1;
+    walk_object(self, node);
+  }
+
+  fn visit_key(&mut self, key: &Identifier) {
+    self.keys.push(key.decoded().into_owned());
+  }
+
+  fn visit_string(&mut self, _node: &Node) {
+    self.strings += 1;
+  }
+
+  fn visit_number(&mut self, _node: &Node) {
+    self.numbers += 1;
+  }
+
+  fn visit_boolean(&mut self, _node: &Node) {
+    self.others += 1;
+  }
+
+  fn visit_null(&mut self, _node: &Node) {
+    self.others += 1;
+  }
+}
+
+#[test]
+fn visitor_walks_every_node() {
+  let mut counter = Counter {
+    keys: vec![],
+    strings: 0,
+    numbers: 0,
+    objects: 0,
+    others: 0,
+  };
+  counter.visit_node(&tree(DOC));
+
+  assert_eq!(
+    counter.keys,
+    vec!["user", "name", "password", "tags", "db", "password", "port", "tls", "ca"]
+  );
+  assert_eq!(counter.objects, 3);
+  assert_eq!(counter.strings, 5);
+  assert_eq!(counter.numbers, 2);
+  assert_eq!(counter.others, 2);
+}
+
+struct Redact;
+
+impl VisitorMut for Redact {
+  fn visit_property_mut(&mut self, property: &mut Property) {
+    if property.key.matches("password") {
+      let span = property.value.span();
+      property.value = Node::Null {
+        raw: "null".to_string(),
+        span,
+      };
+    }
+    walk_property_mut(self, property);
+  }
+}
+
+#[test]
+fn visitor_mut_rewrites_nodes() {
+  let mut doc = tree(DOC);
+  Redact.visit_node_mut(&mut doc);
+
+  assert!(doc["user"]["password"].is_null());
+  assert!(doc["db"]["password"].is_null());
+  assert_eq!(doc["user"]["name"].decoded().unwrap(), "ada");
+  assert_eq!(doc["db"]["password"].span().start.line, 4);
+}